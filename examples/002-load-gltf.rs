@@ -1,61 +1,47 @@
-use bevy::{
-    pbr::{CascadeShadowConfigBuilder, DirectionalLightShadowMap},
-    prelude::*,
-};
+// Features a glTF model viewer. Pass another model to preview it:
+// cargo run --example 002-load-gltf -- models/cube.gltf
+
+use bevy::prelude::*;
 use std::f32::consts::*;
 
+use bevy_game_bits::gltf_animation::AnimationFinished;
+use bevy_game_bits::gltf_hooks::{GltfHooksPlugin, GltfNodeHooks};
+use bevy_game_bits::model_viewer::ModelViewerPlugin;
+
+const DEFAULT_MODEL_PATH: &str = "models/cube.glb";
+
+// Attached by name to the "cube" node of the glTF file, once the scene has spawned.
+#[derive(Component)]
+struct Spinning;
+
 fn main() {
+    let model = std::env::args()
+        .nth(1)
+        .unwrap_or(DEFAULT_MODEL_PATH.to_string());
+
     App::new()
-        .insert_resource(DirectionalLightShadowMap { size: 4096 })
         .add_plugins(DefaultPlugins)
+        .add_plugins(GltfHooksPlugin)
+        .add_plugins(ModelViewerPlugin { model })
         .add_systems(Startup, setup)
-        .add_systems(Update, animate_light_direction)
+        .add_systems(Update, (spin, log_finished_animations))
         .run();
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn((
-        Camera3d::default(),
-        Transform::from_xyz(0.7, 0.7, 1.0).looking_at(Vec3::new(0.0, 0.3, 0.0), Vec3::Y),
-        EnvironmentMapLight {
-            diffuse_map: asset_server.load("environment_maps/pisa_diffuse_rgb9e5_zstd.ktx2"),
-            specular_map: asset_server.load("environment_maps/pisa_specular_rgb9e5_zstd.ktx2"),
-            intensity: 250.0,
-            ..default()
-        },
-    ));
-
-    commands.spawn((
-        DirectionalLight {
-            shadows_enabled: true,
-            ..default()
-        },
-        // This is a relatively small scene, so use tighter shadow
-        // cascade bounds than the default for better quality.
-        // We also adjusted the shadow map to be larger since we're
-        // only using a single cascade.
-        CascadeShadowConfigBuilder {
-            num_cascades: 1,
-            maximum_distance: 1.6,
-            ..default()
-        }
-        .build(),
-    ));
-    commands.spawn(SceneRoot(
-        asset_server.load(GltfAssetLabel::Scene(0).from_asset("models/cube.glb")),
-    ));
+fn setup(mut hooks: ResMut<GltfNodeHooks>) {
+    hooks.on_name("cube", |entity, _node| {
+        entity.insert(Spinning);
+    });
 }
 
-fn animate_light_direction(
-    time: Res<Time>,
-    mut query: Query<&mut Transform, With<DirectionalLight>>,
-) {
+fn spin(time: Res<Time>, mut query: Query<&mut Transform, With<Spinning>>) {
     for mut transform in &mut query {
-        transform.rotation = Quat::from_euler(
-            EulerRot::ZYX,
-            0.0,
-            time.elapsed_secs() * PI / 5.0,
-            -FRAC_PI_4,
-        );
+        transform.rotate_y(time.delta_secs() * PI / 4.0);
+    }
+}
+
+fn log_finished_animations(mut events: EventReader<AnimationFinished>) {
+    for event in events.read() {
+        info!("Animation {} finished", event.clip);
     }
 }
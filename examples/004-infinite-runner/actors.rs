@@ -1,135 +1,200 @@
+use bevy::audio::Volume;
 use bevy::prelude::*;
+use serde::Deserialize;
 
+use bevy_game_bits::aseprite::AsepriteAnimation;
+use bevy_game_bits::cleanup::DespawnOnExit;
+use bevy_game_bits::collision::{CollisionLayers, Hitbox};
+use bevy_game_bits::difficulty::{Difficulty, DifficultySettings};
+use bevy_game_bits::health::Health;
 use bevy_game_bits::jump;
+use bevy_game_bits::parallax::{spawn_parallax_layer, ParallaxLayer};
+use bevy_game_bits::pool::{HandleCache, ReleaseExt};
+use bevy_game_bits::ron_asset::RonAssetPlugin;
+use bevy_game_bits::scoring::Scoring;
+use bevy_game_bits::scroll::{Scrolling, WorldScroll};
+use bevy_game_bits::spawner::{SpawnPosition, SpawnTier, Spawner};
+use bevy_game_bits::sprite_animation::{JumpAnimations, SpriteFrameEvent};
 
-use crate::colors::*;
-use crate::ui::{
-    InstructionsText, Score, ScoreText, WindowSize, SCOREBOARD_FONT_SIZE, SCOREBOARD_TEXT_PADDING,
-};
+use crate::game_state::GameStates;
+use crate::score::Clearance;
+use crate::ui::{LivesText, ScoreText, WindowSize, SCOREBOARD_FONT_SIZE, SCOREBOARD_TEXT_PADDING};
 
 const PLAYER_WIDTH: f32 = 32.0;
 const PLAYER_HEIGHT: f32 = 32.0;
-const OBSTACLE_WIDTH: f32 = 8.0;
-const OBSTACLE_HEIGHT: f32 = 72.0;
+// Narrower than the sprite, whose frames have some empty space around the dino.
+const PLAYER_HITBOX: Vec2 = Vec2::new(20.0, 28.0);
+const PLAYER_LIVES: u32 = 3;
+// Seconds, long enough for the obstacle that hit to scroll past.
+const PLAYER_INVULNERABILITY: f32 = 1.5;
+// Times per second the player blinks while invulnerable.
+const PLAYER_BLINK_RATE: f32 = 10.0;
+
+// Collision layers, only the player and obstacles are tested against each other.
+pub const PLAYER_LAYER: u32 = 1 << 0;
+pub const OBSTACLE_LAYER: u32 = 1 << 1;
+
+// Frames and durations come from its "run", "jump", "fall" and "land" tags.
+const PLAYER_ANIMATION: &str = "sprites/platformer/dino.aseprite";
+
+const FOOTSTEP_SOUND: &str = "sfx/jsfxr/click.wav";
+// Quiet, there are a couple every run cycle.
+const FOOTSTEP_VOLUME: f32 = 0.3;
 
 const HORIZON_HEIGHT: f32 = 32.0;
 
-const DIFFICULTY_MULTIPLIER: f32 = 0.98;
+// Tune the tiers there while the game runs, with `--features dev`.
+const TIERS_FILE: &str = "runner/infinite-runner.tiers.ron";
 
 pub struct ActorsPlugin;
 
 impl Plugin for ActorsPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(BackgroundElements {
-            tiers: vec![
-                // Small Clouds
-                ElementTier::new(
-                    0.0,
-                    20.0,
-                    0.6,
-                    Vec2::new(-16.0, 1.0),
-                    Vec2::new(28.0, 16.0),
-                    COLOR_GRAY,
-                ),
-                // Large Clouds
-                ElementTier::with_default_transform(
-                    1.0,
-                    20.0,
-                    0.6,
-                    Vec2::new(72.0, 36.0),
-                    COLOR_DARKGREY,
-                ),
-            ],
-        })
-        .insert_resource(Obstacles {
-            tiers: vec![
-                // Cactus
-                ElementTier::with_default_transform(
-                    0.0,
-                    4.0,
-                    8.,
-                    Vec2::new(OBSTACLE_WIDTH, OBSTACLE_HEIGHT),
-                    COLOR_WHITE,
-                ),
-                // Stone
-                ElementTier::with_default_transform(
-                    5.0,
-                    12.0,
-                    8.,
-                    Vec2::new(56.0, 56.0),
-                    COLOR_GRAY,
-                ),
-                // Bike
-                ElementTier::with_default_transform(
-                    9.0,
-                    18.0,
-                    10.,
-                    Vec2::new(72.0, 48.0),
-                    COLOR_WHITE,
-                ),
-            ],
-        });
+        app.add_plugins(RonAssetPlugin::<RunnerTiers>::new(&["tiers.ron"]))
+            .init_resource::<HandleCache<Mesh>>()
+            .init_resource::<HandleCache<ColorMaterial>>()
+            .add_systems(Startup, load_runner_tiers)
+            .add_systems(
+                Update,
+                (reload_tiers, blink_invulnerable_player, play_footsteps)
+                    .run_if(in_state(GameStates::Play)),
+            );
     }
 }
 
-#[derive(Component)]
-pub struct Velocity(f32);
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct RunnerTiers {
+    // Pixels per second, before difficulty.
+    scroll_speed: f32,
+    difficulty: DifficultySettings,
+    obstacles: Vec<ElementTier>,
+    background_elements: Vec<ElementTier>,
+    #[serde(default)]
+    layers: Vec<LayerConfig>,
+}
+
+#[derive(Resource)]
+pub struct RunnerTiersHandle(Handle<RunnerTiers>);
 
-#[derive(Debug)]
+#[derive(Deserialize, Debug)]
+pub enum Appearance {
+    // Hexadecimal sRGB, such as "#b3b3b3".
+    Color(String),
+    // Image path, stretched to the tier size.
+    Sprite(String),
+}
+
+// Tiling background, scrolling at `depth` times the scroll speed.
+#[derive(Deserialize, Debug)]
+pub struct LayerConfig {
+    image: String,
+    depth: f32,
+    // Y of the top edge.
+    top: f32,
+    #[serde(default)]
+    z: f32,
+}
+
+#[derive(Deserialize, Debug)]
 pub struct ElementTier {
-    spawn_cooldown: f32,
-    default_spawn_cooldown: f32,
-    velocity: f32,
-    transform: Vec2,
-    size: Vec2,
-    color: Color,
+    name: String,
+    // Seconds before the first spawn, then between spawns.
+    #[serde(default)]
+    initial_delay: f32,
+    cooldown: f32,
+    // Up to that many seconds are randomly added to or removed from each cooldown.
+    #[serde(default)]
+    jitter: f32,
+    // Fraction of the scroll speed.
+    #[serde(default = "ground_scroll_factor")]
+    scroll_factor: f32,
+    // Vertical offset from where the tier kind spawns, and draw order.
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    depth: f32,
+    size: (f32, f32),
+    // Obstacle collision box, the size by default. Bottom aligned with the sprite.
+    #[serde(default)]
+    hitbox: Option<(f32, f32)>,
+    appearance: Appearance,
+    // Difficulty level from which the tier spawns.
+    #[serde(default)]
+    unlock_level: f32,
+}
+
+fn ground_scroll_factor() -> f32 {
+    1.0
 }
 
 impl ElementTier {
-    pub fn with_default_transform(
-        spawn_cooldown: f32,
-        default_spawn_cooldown: f32,
-        velocity: f32,
-        size: Vec2,
-        color: Color,
-    ) -> Self {
-        Self {
-            spawn_cooldown,
-            default_spawn_cooldown,
-            velocity,
-            transform: Vec2::new(0.0, 0.0),
-            size,
-            color,
-        }
+    fn size(&self) -> Vec2 {
+        Vec2::new(self.size.0, self.size.1)
     }
 
-    pub fn new(
-        spawn_cooldown: f32,
-        default_spawn_cooldown: f32,
-        velocity: f32,
-        transform: Vec2,
-        size: Vec2,
-        color: Color,
-    ) -> Self {
-        Self {
-            spawn_cooldown,
-            default_spawn_cooldown,
-            velocity,
-            transform,
-            size,
-            color,
+    fn hitbox(&self) -> Hitbox {
+        let size = self.size();
+        let hitbox = self.hitbox.map_or(size, |(x, y)| Vec2::new(x, y));
+
+        Hitbox::new(hitbox).with_offset(Vec2::new(0.0, (hitbox.y - size.y) / 2.))
+    }
+
+    fn sprite(&self, asset_server: &AssetServer) -> Sprite {
+        let sprite = Sprite {
+            custom_size: Some(self.size()),
+            ..default()
+        };
+
+        match &self.appearance {
+            Appearance::Color(hex) => Sprite {
+                color: Srgba::hex(hex)
+                    .unwrap_or_else(|error| {
+                        warn!("Invalid color {} on tier {}: {}", hex, self.name, error);
+                        Srgba::WHITE
+                    })
+                    .into(),
+                ..sprite
+            },
+            Appearance::Sprite(path) => Sprite {
+                image: asset_server.load(path),
+                ..sprite
+            },
         }
     }
-}
 
-#[derive(Resource)]
-pub struct BackgroundElements {
-    tiers: Vec<ElementTier>,
+    fn spawn_tier(&self, position: Vec3, kind: Kinds, sprite: Sprite) -> SpawnTier {
+        let factor = self.scroll_factor;
+        let hitbox = self.hitbox();
+
+        SpawnTier::new(self.name.clone(), self.cooldown, move |entity, _context| {
+            // Released rather than despawned once the run is over, see `release_elements`.
+            entity.insert((Scrolling { factor }, sprite.clone()));
+
+            match kind {
+                Kinds::Obstacle => entity.insert((
+                    Obstacle,
+                    hitbox,
+                    CollisionLayers::new(OBSTACLE_LAYER, PLAYER_LAYER),
+                    Clearance::default(),
+                )),
+                Kinds::BackgroundElement => entity.insert(BackgroundElement),
+            };
+        })
+        .with_initial_delay(self.initial_delay)
+        .with_jitter(self.jitter)
+        .with_pool(self.name.clone())
+        .at(SpawnPosition::Fixed(
+            position + Vec3::new(0.0, self.height, self.depth),
+        ))
+    }
 }
 
-#[derive(Resource)]
-pub struct Obstacles {
-    tiers: Vec<ElementTier>,
+// Ties a spawner to the difficulty level, with the unlock level of each of its tiers.
+#[derive(Component)]
+pub struct DifficultyScaling {
+    unlock_levels: Vec<f32>,
+    // Whether spawns also speed up with the difficulty.
+    scale_rate: bool,
 }
 
 #[derive(Component)]
@@ -141,48 +206,77 @@ pub struct Obstacle;
 #[derive(Component)]
 pub struct BackgroundElement;
 
+#[derive(Clone, Copy)]
 enum Kinds {
     BackgroundElement,
     Obstacle,
 }
 
-#[derive(Component)]
-pub struct Kind(Kinds);
-
 pub fn spawn_scene_and_player(
     mut commands: Commands,
     window_size: Res<WindowSize>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    asset_server: Res<AssetServer>,
+    (mut meshes, mut mesh_cache): (ResMut<Assets<Mesh>>, ResMut<HandleCache<Mesh>>),
+    (mut materials, mut material_cache): (
+        ResMut<Assets<ColorMaterial>>,
+        ResMut<HandleCache<ColorMaterial>>,
+    ),
 ) {
+    // Every run spawns these again, with the same assets.
+    let horizon_mesh = mesh_cache.get_or_add("horizon", &mut meshes, || {
+        Rectangle::new(window_size.0, 1.0).into()
+    });
+    let horizon_material = material_cache.get_or_add("horizon", &mut materials, || {
+        Color::srgb(1.0, 1.0, 1.0).into()
+    });
+
     // Player
     commands.spawn((
         Player,
+        DespawnOnExit::new(GameStates::Play),
         jump::JumpingState::default(),
-        Transform {
-            translation: Vec3::new(
-                0.0 - (window_size.0 / 2.0) + (window_size.0 / 6.0),
-                0.0,
-                1.0,
-            ),
-            scale: Vec3::new(PLAYER_WIDTH, PLAYER_HEIGHT, 1.0),
-            ..Default::default()
+        Health::new(PLAYER_LIVES).with_invulnerability(PLAYER_INVULNERABILITY),
+        AsepriteAnimation::new(asset_server.load(PLAYER_ANIMATION))
+            // Feet touching the ground.
+            .with_event("run", 1, "footstep")
+            .with_event("run", 4, "footstep"),
+        JumpAnimations::new("run", "jump", "fall", "land"),
+        // Feet on the ground, where the sprite bottom is.
+        Hitbox::new(PLAYER_HITBOX)
+            .with_offset(Vec2::new(0.0, (PLAYER_HITBOX.y - PLAYER_HEIGHT) / 2.0)),
+        CollisionLayers::new(PLAYER_LAYER, OBSTACLE_LAYER),
+        Transform::from_xyz(
+            0.0 - (window_size.0 / 2.0) + (window_size.0 / 6.0),
+            0.0,
+            1.0,
+        ),
+        Sprite {
+            custom_size: Some(Vec2::new(PLAYER_WIDTH, PLAYER_HEIGHT)),
+            // The atlas of the file, shown as soon as it is loaded.
+            ..Sprite::from_atlas_image(
+                asset_server.load(format!("{}#image", PLAYER_ANIMATION)),
+                TextureAtlas {
+                    layout: asset_server.load(format!("{}#layout", PLAYER_ANIMATION)),
+                    index: 0,
+                },
+            )
         },
-        Mesh2d(meshes.add(Rectangle::new(1.0, 1.0))),
-        MeshMaterial2d(materials.add(Color::srgb(1.0, 1.0, 1.0))),
     ));
 
     // Horiwon
     commands.spawn((
+        DespawnOnExit::new(GameStates::Play),
         Transform::from_xyz(0.0, HORIZON_HEIGHT, -1.0),
-        Mesh2d(meshes.add(Rectangle::new(window_size.0, 1.0))),
-        MeshMaterial2d(materials.add(Color::srgb(1.0, 1.0, 1.0))),
+        Mesh2d(horizon_mesh),
+        MeshMaterial2d(horizon_material),
     ));
 
-    // Score
+    // Score, still shown once the game is over.
     commands.spawn((
         ScoreText,
-        InstructionsText,
+        // Quitting or restarting from the pause menu skips the game over screen, see
+        // `despawn_score_text`.
+        DespawnOnExit::new(GameStates::GameOver),
         Text::new("0"),
         TextFont {
             font_size: SCOREBOARD_FONT_SIZE,
@@ -196,108 +290,363 @@ pub fn spawn_scene_and_player(
             ..default()
         },
     ));
+
+    commands.spawn((
+        LivesText,
+        DespawnOnExit::new(GameStates::Play),
+        Text::new(""),
+        TextFont {
+            font_size: SCOREBOARD_FONT_SIZE,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: SCOREBOARD_TEXT_PADDING,
+            left: SCOREBOARD_TEXT_PADDING,
+            ..default()
+        },
+    ));
+}
+
+pub fn load_runner_tiers(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(RunnerTiersHandle(asset_server.load(TIERS_FILE)));
 }
 
-pub fn spawn_obstacles(
+fn spawn_tier_entities(
+    commands: &mut Commands,
+    window_size: &WindowSize,
+    asset_server: &AssetServer,
+    tiers: &RunnerTiers,
+) {
+    let unlock_levels =
+        |tiers: &[ElementTier]| tiers.iter().map(|tier| tier.unlock_level).collect();
+
+    let obstacle_tiers = tiers
+        .obstacles
+        .iter()
+        .map(|tier| {
+            tier.spawn_tier(
+                Vec3::new(window_size.0 * 2., (tier.size.1 - PLAYER_HEIGHT) / 2., 0.0),
+                Kinds::Obstacle,
+                tier.sprite(asset_server),
+            )
+        })
+        .collect();
+
+    let background_tiers = tiers
+        .background_elements
+        .iter()
+        .map(|tier| {
+            tier.spawn_tier(
+                Vec3::new(window_size.0, window_size.1 / 3., 0.0),
+                Kinds::BackgroundElement,
+                tier.sprite(asset_server),
+            )
+        })
+        .collect();
+
+    commands.spawn((
+        Spawner::new(obstacle_tiers).with_stream("obstacles"),
+        DifficultyScaling {
+            unlock_levels: unlock_levels(&tiers.obstacles),
+            scale_rate: true,
+        },
+        DespawnOnExit::new(GameStates::Play),
+    ));
+    commands.spawn((
+        Spawner::new(background_tiers).with_stream("background"),
+        DifficultyScaling {
+            unlock_levels: unlock_levels(&tiers.background_elements),
+            scale_rate: false,
+        },
+        DespawnOnExit::new(GameStates::Play),
+    ));
+
+    for layer in tiers.layers.iter() {
+        let entity = spawn_parallax_layer(
+            commands,
+            asset_server.load(&layer.image),
+            ParallaxLayer::new(layer.depth, window_size.0, layer.top),
+            layer.z,
+        );
+        commands
+            .entity(entity)
+            .insert(DespawnOnExit::new(GameStates::Play));
+    }
+}
+
+// Spawners and layers are rebuilt from the tiers on each run, so that runs (and their replays)
+// start from the initial cooldowns.
+pub fn spawn_tiers(
     mut commands: Commands,
     window_size: Res<WindowSize>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    time: Res<Time>,
-    obstacles_resource: ResMut<Obstacles>,
+    asset_server: Res<AssetServer>,
+    handle: Res<RunnerTiersHandle>,
+    tiers: Res<Assets<RunnerTiers>>,
+    mut difficulty: ResMut<Difficulty>,
+    mut world_scroll: ResMut<WorldScroll>,
 ) {
-    let elapsed_secs = time.delta().as_secs_f32();
-    let obstacles = obstacles_resource.into_inner();
-
-    for tier in obstacles.tiers.iter_mut() {
-        tier.spawn_cooldown -= elapsed_secs;
-        if tier.spawn_cooldown < 0. {
-            commands.spawn((
-                Obstacle,
-                Kind(Kinds::Obstacle),
-                Velocity(tier.velocity),
-                Transform {
-                    translation: Vec3::new(
-                        window_size.0 * 2.,
-                        (tier.size.y - PLAYER_HEIGHT) / 2.,
-                        0.0,
-                    ),
-                    scale: Vec3::new(tier.size.x, tier.size.y, 1.),
-                    ..Default::default()
-                },
-                Mesh2d(meshes.add(Rectangle::new(1., 1.))),
-                MeshMaterial2d(materials.add(tier.color)),
-            ));
+    // Otherwise they are built by `reload_tiers` once loaded.
+    if let Some(tiers) = tiers.get(&handle.0) {
+        difficulty.settings = tiers.difficulty.clone();
+        world_scroll.base_speed = tiers.scroll_speed;
+        spawn_tier_entities(&mut commands, &window_size, &asset_server, tiers);
+    }
+}
 
-            tier.default_spawn_cooldown = tier.default_spawn_cooldown * DIFFICULTY_MULTIPLIER;
-            tier.spawn_cooldown = tier.default_spawn_cooldown;
+// Picks up the tiers as soon as they are loaded, and again each time the file is saved. Elements
+// already on screen are left as they are.
+pub fn reload_tiers(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<RunnerTiers>>,
+    handle: Res<RunnerTiersHandle>,
+    tier_entities: Query<Entity, Or<(With<Spawner>, With<ParallaxLayer>)>>,
+) {
+    let changed = events.read().any(|event| match event {
+        AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+            *id == handle.0.id()
         }
+        _ => false,
+    });
+
+    if !changed {
+        return;
     }
+
+    for entity in tier_entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    commands.run_system_cached(spawn_tiers);
 }
 
-pub fn spawn_background_elements(
+pub fn apply_difficulty(
+    mut spawners: Query<(&mut Spawner, &DifficultyScaling)>,
+    mut difficulty: ResMut<Difficulty>,
+    mut world_scroll: ResMut<WorldScroll>,
+    scoring: Res<Scoring>,
+) {
+    difficulty.set_score(scoring.total());
+    world_scroll.speed_multiplier = difficulty.speed();
+
+    for (mut spawner, scaling) in spawners.iter_mut() {
+        if scaling.scale_rate {
+            spawner.rate = difficulty.spawn_rate();
+        }
+
+        for (tier, level) in spawner.tiers.iter_mut().zip(scaling.unlock_levels.iter()) {
+            tier.active = difficulty.is_unlocked(*level);
+        }
+    }
+}
+
+pub fn despawn_passed_elements(
     mut commands: Commands,
     window_size: Res<WindowSize>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    time: Res<Time>,
-    elements_resource: ResMut<BackgroundElements>,
+    elements: Query<(Entity, &Transform, &Sprite), Or<(With<Obstacle>, With<BackgroundElement>)>>,
 ) {
-    let elapsed_secs = time.delta().as_secs_f32();
-    let elements = elements_resource.into_inner();
-
-    for tier in elements.tiers.iter_mut() {
-        tier.spawn_cooldown -= elapsed_secs;
-
-        if tier.spawn_cooldown < 0. {
-            commands.spawn((
-                BackgroundElement,
-                Kind(Kinds::BackgroundElement),
-                Velocity(tier.velocity),
-                Transform {
-                    translation: Vec3::new(
-                        window_size.0,
-                        (window_size.1 / 3.) + tier.transform.x,
-                        tier.transform.y,
-                    ),
-                    scale: Vec3::new(tier.size.x, tier.size.y, 1.),
-                    ..Default::default()
-                },
-                Mesh2d(meshes.add(Rectangle::new(1., 1.))),
-                MeshMaterial2d(materials.add(tier.color)),
-            ));
+    for (entity, transform, sprite) in elements.iter() {
+        let width = sprite.custom_size.map_or(0., |size| size.x);
 
-            tier.spawn_cooldown = tier.default_spawn_cooldown;
+        if transform.translation.x < (-window_size.0 / 2.) - width / 2. {
+            commands.entity(entity).release();
         }
     }
 }
 
-pub fn despawn_entities(
+// Elements still on screen when the run ends go back to their pools, for the next run.
+pub fn release_elements(
     mut commands: Commands,
-    query: Query<Entity, Or<(With<Player>, With<Obstacle>, With<BackgroundElement>)>>,
+    elements: Query<Entity, Or<(With<Obstacle>, With<BackgroundElement>)>>,
 ) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn();
+    for entity in elements.iter() {
+        commands.entity(entity).release();
     }
 }
 
-pub fn move_moving_elements(
+pub fn blink_invulnerable_player(player: Single<(&Health, &mut Visibility), With<Player>>) {
+    let (health, mut visibility) = player.into_inner();
+
+    let hidden =
+        health.is_invulnerable() && (health.invulnerable_for() * PLAYER_BLINK_RATE) as u32 % 2 == 1;
+
+    visibility.set_if_neq(if hidden {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    });
+}
+
+pub fn play_footsteps(
     mut commands: Commands,
-    window_size: Res<WindowSize>,
-    mut obstacles: Query<(Entity, &Kind, &mut Velocity, &mut Transform)>,
-    mut score: ResMut<Score>,
+    mut events: EventReader<SpriteFrameEvent>,
+    player: Single<Entity, With<Player>>,
+    asset_server: Res<AssetServer>,
 ) {
-    for (entity, kind, velocity, mut transform) in obstacles.iter_mut() {
-        if transform.translation.x < (-window_size.0 / 2.) - OBSTACLE_WIDTH / 2. {
-            match kind.0 {
-                Kinds::Obstacle => {
-                    score.0 += 100;
-                }
-                Kinds::BackgroundElement => {}
-            }
-            commands.entity(entity).despawn();
-        } else {
-            transform.translation.x -= velocity.0;
+    for _ in events
+        .read()
+        .filter(|event| event.entity == *player && event.name == "footstep")
+    {
+        commands.spawn((
+            AudioPlayer::new(asset_server.load(FOOTSTEP_SOUND)),
+            PlaybackSettings::DESPAWN.with_volume(Volume::new(FOOTSTEP_VOLUME)),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    use bevy::asset::AssetPlugin;
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+
+    use bevy_game_bits::aseprite::AsepritePlugin;
+    use bevy_game_bits::cleanup::CleanupPlugin;
+    use bevy_game_bits::difficulty::{reset_difficulty, DifficultyPlugin, DifficultySet};
+    use bevy_game_bits::rng::GameRng;
+    use bevy_game_bits::scroll::{ScrollPlugin, ScrollSet};
+    use bevy_game_bits::spawner::{SpawnerPlugin, SpawnerSet};
+
+    use super::*;
+
+    const FRAME_TIME: Duration = Duration::from_nanos(16_666_667);
+    // A minute at 60 frames per second.
+    const FRAMES_PER_RUN: u32 = 3_600;
+    const RUNS: usize = 5;
+    // Every run spawns the same way, as when replaying a seed.
+    const SEED: u64 = 42;
+
+    // Every obstacle entity ever spawned, which only grows when the pool has none to give back.
+    #[derive(Resource, Default)]
+    struct SpawnedObstacles(HashSet<Entity>);
+
+    #[derive(Debug, PartialEq)]
+    struct Counts {
+        entities: u32,
+        spawned_obstacles: usize,
+        meshes: usize,
+        materials: usize,
+    }
+
+    fn reseed(mut rng: ResMut<GameRng>) {
+        rng.reseed(SEED);
+    }
+
+    fn track_obstacles(
+        mut spawned: ResMut<SpawnedObstacles>,
+        obstacles: Query<Entity, Added<Obstacle>>,
+    ) {
+        spawned.0.extend(obstacles.iter());
+    }
+
+    // The spawning half of `GameStatePlugin`, with the tiers shipped with the game.
+    fn app() -> App {
+        let tiers: RunnerTiers = ron::de::from_str(include_str!(
+            "../../assets/runner/infinite-runner.tiers.ron"
+        ))
+        .unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            StatesPlugin,
+            CleanupPlugin::<GameStates>::default(),
+            SpawnerPlugin,
+            DifficultyPlugin,
+            ScrollPlugin,
+            AsepritePlugin,
+        ))
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_asset::<RunnerTiers>()
+        .init_resource::<HandleCache<Mesh>>()
+        .init_resource::<HandleCache<ColorMaterial>>()
+        .init_resource::<Scoring>()
+        .init_resource::<SpawnedObstacles>()
+        .insert_resource(WindowSize(800.0, 600.0))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+        .init_state::<GameStates>()
+        .add_systems(
+            OnEnter(GameStates::Play),
+            (
+                spawn_scene_and_player,
+                (reseed, reset_difficulty, spawn_tiers).chain(),
+            ),
+        )
+        .add_systems(OnExit(GameStates::Play), release_elements)
+        .configure_sets(
+            FixedUpdate,
+            (
+                DifficultySet.before(apply_difficulty),
+                SpawnerSet.after(apply_difficulty),
+                ScrollSet.after(SpawnerSet).before(despawn_passed_elements),
+            )
+                .run_if(in_state(GameStates::Play)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                apply_difficulty,
+                track_obstacles.after(SpawnerSet),
+                despawn_passed_elements,
+            )
+                .run_if(in_state(GameStates::Play)),
+        );
+
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<RunnerTiers>>()
+            .add(tiers);
+        app.insert_resource(RunnerTiersHandle(handle));
+
+        app.finish();
+        app.cleanup();
+        app
+    }
+
+    // Plays a run until the game is over, and counts what is left.
+    fn run(app: &mut App) -> Counts {
+        app.world_mut()
+            .resource_mut::<NextState<GameStates>>()
+            .set(GameStates::Play);
+        for _ in 0..FRAMES_PER_RUN {
+            app.update();
+        }
+
+        app.world_mut()
+            .resource_mut::<NextState<GameStates>>()
+            .set(GameStates::GameOver);
+        app.update();
+
+        let world = app.world();
+        Counts {
+            entities: world.entities().len(),
+            spawned_obstacles: world.resource::<SpawnedObstacles>().0.len(),
+            meshes: world.resource::<Assets<Mesh>>().len(),
+            materials: world.resource::<Assets<ColorMaterial>>().len(),
+        }
+    }
+
+    #[test]
+    fn runs_reuse_obstacles_and_assets() {
+        let mut app = app();
+        let runs: Vec<Counts> = (0..RUNS).map(|_| run(&mut app)).collect();
+
+        assert!(runs[0].spawned_obstacles > 1, "runs: {:?}", runs);
+        // The first run fills the pools, the others only reuse them.
+        for counts in runs.iter().skip(1) {
+            assert_eq!(*counts, runs[1], "runs: {:?}", runs);
         }
+        assert_eq!(
+            (runs[0].meshes, runs[0].materials),
+            (runs[1].meshes, runs[1].materials)
+        );
     }
 }
@@ -1,5 +0,0 @@
-use bevy::color::Color;
-
-pub const COLOR_WHITE: Color = Color::srgb(1.0, 1.0, 1.0);
-pub const COLOR_GRAY: Color = Color::srgb(0.7, 0.7, 0.7);
-pub const COLOR_DARKGREY: Color = Color::srgb(0.4, 0.4, 0.4);
@@ -1,13 +1,45 @@
 use bevy::prelude::*;
 
+use bevy_game_bits::actions::{Action, Actions};
+use bevy_game_bits::aseprite::AsepritePlugin;
+use bevy_game_bits::cleanup::CleanupPlugin;
+use bevy_game_bits::collision::{CollisionPlugin, CollisionSet, CollisionStarted};
+use bevy_game_bits::difficulty::{reset_difficulty, DifficultyPlugin, DifficultySet};
+use bevy_game_bits::health::{DamageEvent, Died, HealthPlugin, HealthSet};
 use bevy_game_bits::jump;
+use bevy_game_bits::parallax::ParallaxPlugin;
+use bevy_game_bits::replay::{InputRecorder, InputRecording, ReplayPlugin};
+use bevy_game_bits::rng::GameRng;
+use bevy_game_bits::scoring::{Scoring, ScoringPlugin};
+use bevy_game_bits::scroll::{ScrollPlugin, ScrollSet};
+use bevy_game_bits::spawner::{SpawnerPlugin, SpawnerSet};
+use bevy_game_bits::sprite_animation::SpriteAnimationPlugin;
+use bevy_game_bits::virtual_controls::{spawn_virtual_button, VirtualControlsPlugin};
 
 use crate::actors::*;
-use crate::collision::{detect_collisions, CollisionEvent};
+use crate::leaderboard::{InitialsEntry, LeaderboardPlugin};
+use crate::passability::validate_obstacle_spawns;
+use crate::pause::PausePlugin;
+use crate::score::{
+    animate_score_popups, break_streak, score_distance, score_passed_obstacles, spawn_score_popups,
+};
 use crate::ui::*;
 
 const SCREEN_UNIT: f32 = 10.0;
 
+// Every run is recorded here, play it back with `-- --replay infinite-runner.replay`.
+const REPLAY_FILE: &str = "infinite-runner.replay";
+
+const JUMP_BUTTON_SIZE: Val = Val::Px(96.0);
+const JUMP_BUTTON_MARGIN: Val = Val::Px(24.0);
+
+#[derive(Resource)]
+pub struct ReplayToPlay(pub String);
+
+// Plays every run from this seed instead of a random one.
+#[derive(Resource)]
+pub struct SeedToPlay(pub u64);
+
 #[derive(States, Default, Debug, Clone, Eq, PartialEq, Hash)]
 pub enum GameStates {
     #[default]
@@ -16,53 +48,126 @@ pub enum GameStates {
     GameOver,
 }
 
+// Gameplay only ticks while running, see `pause.rs`.
+#[derive(SubStates, Default, Debug, Clone, Eq, PartialEq, Hash)]
+#[source(GameStates = GameStates::Play)]
+pub enum PlayStates {
+    #[default]
+    Running,
+    Paused,
+}
+
+// Starts a new run straight from the title screen, without waiting for a key press.
+#[derive(Resource)]
+pub struct RestartRequested;
+
 pub struct GameStatePlugin;
 
 impl Plugin for GameStatePlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameStates>()
-            .insert_resource(Score(0))
-            .add_event::<CollisionEvent>()
+            .add_sub_state::<PlayStates>()
+            .add_plugins(CleanupPlugin::<GameStates>::default())
             .add_plugins(ActorsPlugin)
             .add_plugins(jump::JumpPlugin {
                 screen_unit: SCREEN_UNIT,
             })
+            .add_plugins(ReplayPlugin)
+            .add_plugins(VirtualControlsPlugin)
+            .add_plugins(SpawnerPlugin)
+            .add_plugins(DifficultyPlugin)
+            .add_plugins(ScrollPlugin)
+            .add_plugins(ParallaxPlugin)
+            .add_plugins(SpriteAnimationPlugin)
+            .add_plugins(AsepritePlugin)
+            .add_plugins(PausePlugin)
+            .add_plugins(LeaderboardPlugin)
+            .add_plugins(ScoringPlugin)
+            .add_plugins(HealthPlugin)
+            .add_plugins(CollisionPlugin)
             .add_systems(Startup, setup)
             .add_systems(
-                Update,
+                OnEnter(GameStates::InsertCoin),
+                (
+                    despawn_score_text,
+                    spawn_title_screen.run_if(not(resource_exists::<RestartRequested>)),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
                 (maybe_transit_to_play_state).run_if(in_state(GameStates::InsertCoin)),
             )
             .add_systems(
                 OnEnter(GameStates::Play),
-                (maybe_hide_instructions_text, spawn_scene_and_player),
+                (
+                    maybe_hide_instructions_text,
+                    spawn_scene_and_player,
+                    (reset_difficulty, spawn_tiers).chain(),
+                    start_recording_or_replay,
+                ),
             )
             .add_systems(
                 Update,
                 (
-                    maybe_transit_to_game_over,
-                    spawn_obstacles,
-                    spawn_background_elements,
                     update_score_text,
+                    update_lives_text,
+                    (spawn_score_popups, animate_score_popups).chain(),
                 )
-                    .chain()
                     .run_if(in_state(GameStates::Play)),
             )
+            // Everything affecting the outcome of a run is ticked in a fixed order, so that
+            // replaying the recorded actions reproduces it.
+            .configure_sets(
+                FixedUpdate,
+                (
+                    DifficultySet.before(apply_difficulty),
+                    SpawnerSet
+                        .after(apply_difficulty)
+                        .after(jump::update_player_transform)
+                        .before(validate_obstacle_spawns),
+                    ScrollSet
+                        .after(validate_obstacle_spawns)
+                        .before(score_distance),
+                    CollisionSet
+                        .after(despawn_passed_elements)
+                        .before(hurt_player),
+                    HealthSet
+                        .after(hurt_player)
+                        .before(maybe_transit_to_game_over),
+                )
+                    .run_if(in_state(PlayStates::Running)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_difficulty,
+                    validate_obstacle_spawns.before(despawn_passed_elements),
+                    break_streak.after(HealthSet),
+                )
+                    .run_if(in_state(PlayStates::Running)),
+            )
             .add_systems(
                 FixedUpdate,
                 (
                     jump::handle_jumping_state,
                     jump::update_player_velocity,
                     jump::update_player_transform,
-                    move_moving_elements,
-                    detect_collisions,
+                    score_distance,
+                    score_passed_obstacles,
+                    despawn_passed_elements,
+                    hurt_player,
+                    maybe_transit_to_game_over,
                 )
-                    .run_if(in_state(GameStates::Play)),
+                    .chain()
+                    .run_if(in_state(PlayStates::Running)),
             )
-            .add_systems(OnExit(GameStates::Play), despawn_entities)
+            .add_systems(OnExit(GameStates::Play), (save_recording, release_elements))
             .add_systems(OnEnter(GameStates::GameOver), display_game_over_text)
             .add_systems(
-                Update,
-                (maybe_transit_to_play_state).run_if(in_state(GameStates::GameOver)),
+                FixedUpdate,
+                (maybe_transit_to_play_state).run_if(
+                    in_state(GameStates::GameOver).and(not(resource_exists::<InitialsEntry>)),
+                ),
             );
     }
 }
@@ -73,39 +178,115 @@ fn setup(window_size: Res<WindowSize>, mut window: Single<&mut Window>, mut comm
     // Camera
     commands.spawn(Camera2d);
 
+    // On-screen jump button, for touch screens.
+    spawn_virtual_button(
+        &mut commands,
+        Action::Jump,
+        "Jump",
+        Node {
+            position_type: PositionType::Absolute,
+            width: JUMP_BUTTON_SIZE,
+            height: JUMP_BUTTON_SIZE,
+            right: JUMP_BUTTON_MARGIN,
+            bottom: JUMP_BUTTON_MARGIN,
+            ..default()
+        },
+    );
+}
+
+// Also shown when quitting from the pause menu.
+fn spawn_title_screen(window_size: Res<WindowSize>, mut commands: Commands) {
     commands.spawn((
         Text2d::new("Infinite Runner"),
         TextLayout::new_with_justify(JustifyText::Center),
         TextFont::from_font_size(72.0),
         TitleText,
         InstructionsText,
-        Transform::from_xyz(
-            0.0,
-            0.0 + (window.height() / 2.) - (window.height() / 4.),
-            0.0,
-        ),
+        Transform::from_xyz(0.0, 0.0 + (window_size.1 / 2.) - (window_size.1 / 4.), 0.0),
     ));
 
     add_instructions_text(&mut commands);
 }
 
 pub fn maybe_transit_to_play_state(
+    mut commands: Commands,
     mut next_state: ResMut<NextState<GameStates>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut score: ResMut<Score>,
+    actions: Res<Actions>,
+    mut scoring: ResMut<Scoring>,
+    restart: Option<Res<RestartRequested>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Space) {
-        score.0 = 0;
+    if actions.just_pressed(Action::Jump) || restart.is_some() {
+        commands.remove_resource::<RestartRequested>();
+        scoring.reset();
         next_state.set(GameStates::Play);
     }
 }
 
+// Once per obstacle run into, the invulnerability after a hit lets the player through any other.
+pub fn hurt_player(
+    mut collisions: EventReader<CollisionStarted>,
+    player: Single<Entity, With<Player>>,
+    obstacles: Query<(), With<Obstacle>>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let player = *player;
+
+    for CollisionStarted(contact) in collisions.read() {
+        let Some(obstacle) = contact.other(player) else {
+            continue;
+        };
+
+        if obstacles.contains(obstacle) {
+            damage.send(DamageEvent::new(player, 1).from(obstacle));
+        }
+    }
+}
+
 pub fn maybe_transit_to_game_over(
     mut next_state: ResMut<NextState<GameStates>>,
-    mut events: EventReader<CollisionEvent>,
+    mut events: EventReader<Died>,
+    player: Single<Entity, With<Player>>,
 ) {
-    if !events.is_empty() {
-        events.clear();
+    if events.read().any(|event| event.entity == *player) {
         next_state.set(GameStates::GameOver)
     }
 }
+
+pub fn start_recording_or_replay(
+    mut recorder: ResMut<InputRecorder>,
+    mut rng: ResMut<GameRng>,
+    replay: Option<Res<ReplayToPlay>>,
+    seed: Option<Res<SeedToPlay>>,
+) {
+    let seed = seed.map_or_else(GameRng::random_seed, |seed| seed.0);
+
+    let Some(replay) = replay else {
+        rng.reseed(seed);
+        recorder.start_recording(seed);
+        return;
+    };
+
+    match InputRecording::load(&replay.0) {
+        Ok(recording) => {
+            info!("Playing back {} ({} ticks)", replay.0, recording.ticks());
+            rng.reseed(recording.seed);
+            recorder.start_playback(recording);
+        }
+        Err(error) => {
+            warn!("Could not load replay {}: {}", replay.0, error);
+            rng.reseed(seed);
+            recorder.start_recording(seed);
+        }
+    }
+}
+
+pub fn save_recording(mut recorder: ResMut<InputRecorder>) {
+    let Some(recording) = recorder.stop() else {
+        return;
+    };
+
+    match recording.save(REPLAY_FILE) {
+        Ok(()) => info!("Saved run to {}", REPLAY_FILE),
+        Err(error) => warn!("Could not save run to {}: {}", REPLAY_FILE, error),
+    }
+}
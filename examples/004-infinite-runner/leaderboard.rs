@@ -0,0 +1,169 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+
+use bevy_game_bits::high_scores::{HighScore, HighScores, HighScoresPlugin};
+use bevy_game_bits::rng::GameRng;
+use bevy_game_bits::scoring::Scoring;
+
+use crate::game_state::{GameStates, RestartRequested};
+use crate::ui::InstructionsText;
+
+const HIGH_SCORES_FILE: &str = "infinite-runner.scores.ron";
+const HIGH_SCORES_CAPACITY: usize = 5;
+const INITIALS_LENGTH: usize = 3;
+
+// Monospace, so that columns line up.
+const TABLE_FONT: &str = "fonts/FiraMono-Medium.ttf";
+const TABLE_FONT_SIZE: f32 = 14.0;
+const TABLE_LEFT: f32 = -200.0;
+const TABLE_TOP: f32 = -80.0;
+const TABLE_LINE_HEIGHT: f32 = 20.0;
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(HighScoresPlugin {
+            path: HIGH_SCORES_FILE.to_string(),
+            capacity: HIGH_SCORES_CAPACITY,
+        })
+        .add_systems(
+            OnEnter(GameStates::InsertCoin),
+            spawn_high_score_table.run_if(not(resource_exists::<RestartRequested>)),
+        )
+        .add_systems(OnEnter(GameStates::GameOver), start_initials_entry)
+        .add_systems(
+            Update,
+            type_initials.run_if(resource_exists::<InitialsEntry>),
+        );
+    }
+}
+
+// While it exists, keys type initials instead of starting a new run.
+#[derive(Resource)]
+pub struct InitialsEntry {
+    initials: String,
+    score: u32,
+    seed: u64,
+}
+
+#[derive(Component)]
+struct InitialsText;
+
+fn initials_prompt(initials: &str) -> String {
+    format!(
+        "NEW HIGH SCORE! Type your initials: {:_<width$}",
+        initials,
+        width = INITIALS_LENGTH
+    )
+}
+
+fn spawn_high_score_table(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load(TABLE_FONT);
+
+    for (rank, entry) in high_scores.entries().iter().enumerate() {
+        commands.spawn((
+            Text2d::new(format!(
+                "{}. {:<width$} {:>8} {} #{}",
+                rank + 1,
+                entry.name,
+                entry.score,
+                entry.date,
+                entry.seed,
+                width = INITIALS_LENGTH
+            )),
+            TextFont {
+                font: font.clone(),
+                font_size: TABLE_FONT_SIZE,
+                ..default()
+            },
+            Anchor::CenterLeft,
+            InstructionsText,
+            Transform::from_xyz(TABLE_LEFT, TABLE_TOP - rank as f32 * TABLE_LINE_HEIGHT, 0.0),
+        ));
+    }
+}
+
+fn start_initials_entry(
+    mut commands: Commands,
+    high_scores: Res<HighScores>,
+    scoring: Res<Scoring>,
+    rng: Res<GameRng>,
+) {
+    if !high_scores.qualifies(scoring.total()) {
+        commands.run_system_cached(spawn_high_score_table);
+        return;
+    }
+
+    commands.insert_resource(InitialsEntry {
+        initials: String::new(),
+        score: scoring.total(),
+        seed: rng.seed(),
+    });
+
+    commands.spawn((
+        Text2d::new(initials_prompt("")),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont::from_font_size(16.0),
+        InitialsText,
+        InstructionsText,
+        Transform::from_xyz(0.0, TABLE_TOP, 0.0),
+    ));
+}
+
+fn type_initials(
+    mut commands: Commands,
+    mut entry: ResMut<InitialsEntry>,
+    mut high_scores: ResMut<HighScores>,
+    mut keys: EventReader<KeyboardInput>,
+    text: Single<(Entity, &mut Text2d), With<InitialsText>>,
+) {
+    let (text_entity, mut text) = text.into_inner();
+
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &key.logical_key {
+            Key::Character(characters) => {
+                let letters = characters
+                    .chars()
+                    .filter(char::is_ascii_alphanumeric)
+                    .map(|letter| letter.to_ascii_uppercase());
+
+                for letter in letters {
+                    if entry.initials.len() < INITIALS_LENGTH {
+                        entry.initials.push(letter);
+                    }
+                }
+            }
+            Key::Backspace => {
+                entry.initials.pop();
+            }
+            Key::Enter if !entry.initials.is_empty() => {
+                high_scores.insert(HighScore::new(&entry.initials, entry.score, entry.seed));
+                if let Err(error) = high_scores.save() {
+                    warn!("Could not save high scores: {}", error);
+                }
+
+                commands.remove_resource::<InitialsEntry>();
+                commands.entity(text_entity).despawn();
+                commands.run_system_cached(spawn_high_score_table);
+                return;
+            }
+            _ => {}
+        }
+    }
+
+    let prompt = initials_prompt(&entry.initials);
+    if text.0 != prompt {
+        text.0 = prompt;
+    }
+}
@@ -3,9 +3,11 @@
 use bevy::prelude::*;
 
 mod actors;
-mod collision;
-mod colors;
 mod game_state;
+mod leaderboard;
+mod passability;
+mod pause;
+mod score;
 mod ui;
 
 use game_state::*;
@@ -15,8 +17,23 @@ const WINDOW_WIDTH: f32 = 800.0;
 const WINDOW_HEIGHT: f32 = 600.0;
 
 fn main() {
-    App::new()
-        .insert_resource(WindowSize(WINDOW_WIDTH, WINDOW_HEIGHT))
+    let mut app = App::new();
+
+    // cargo run --example 004-infinite-runner -- --replay infinite-runner.replay
+    if let Some(path) = std::env::args().skip_while(|arg| arg != "--replay").nth(1) {
+        app.insert_resource(ReplayToPlay(path));
+    }
+
+    // cargo run --example 004-infinite-runner -- --seed 42
+    if let Some(seed) = std::env::args()
+        .skip_while(|arg| arg != "--seed")
+        .nth(1)
+        .and_then(|seed| seed.parse().ok())
+    {
+        app.insert_resource(SeedToPlay(seed));
+    }
+
+    app.insert_resource(WindowSize(WINDOW_WIDTH, WINDOW_HEIGHT))
         .insert_resource(ClearColor(Color::srgb(0.0, 0.0, 0.0)))
         .add_plugins(DefaultPlugins)
         .add_plugins(GameStatePlugin)
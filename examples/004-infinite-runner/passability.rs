@@ -0,0 +1,236 @@
+use bevy::math::bounding::BoundingVolume;
+use bevy::prelude::*;
+
+use bevy_game_bits::collision::Hitbox;
+use bevy_game_bits::jump::JumpConfig;
+use bevy_game_bits::pool::ReleaseExt;
+use bevy_game_bits::scroll::{Scrolling, WorldScroll};
+
+use crate::actors::{Obstacle, Player};
+
+// Extra seconds left between two jumps, for speed-ups happening while obstacles close in.
+const SAFETY_MARGIN: f32 = 0.05;
+
+// When an obstacle would be under the player, in seconds from now.
+#[derive(Clone, Copy)]
+struct Crossing {
+    arrives: f32,
+    leaves: f32,
+    // When the player can take off and still clear the obstacle.
+    earliest_jump: f32,
+    latest_jump: f32,
+}
+
+impl Crossing {
+    fn new(
+        (player, player_hitbox): (&Transform, &Hitbox),
+        (obstacle, obstacle_hitbox): (&Transform, &Hitbox),
+        speed: f32,
+        jump: &JumpConfig,
+    ) -> Option<Self> {
+        let player_box = player_hitbox.aabb(player);
+        let obstacle_box = obstacle_hitbox.aabb(obstacle);
+
+        let half_width = player_box.half_size().x + obstacle_box.half_size().x;
+        let distance = obstacle_box.center().x - player_box.center().x;
+        // From where the player stands, whether or not it is jumping already.
+        let height = obstacle_box.max.y - (player_box.min.y - player.translation.y);
+
+        let (rises, falls) = jump.time_above(height)?;
+        let arrives = (distance - half_width) / speed;
+        let leaves = (distance + half_width) / speed;
+
+        let crossing = Self {
+            arrives,
+            leaves,
+            earliest_jump: leaves - falls,
+            latest_jump: arrives - rises,
+        };
+
+        (crossing.earliest_jump <= crossing.latest_jump).then_some(crossing)
+    }
+
+    // Whether one jump clears both obstacles, or the player has time to land in between.
+    fn is_passable_with(&self, other: &Crossing, airtime: f32) -> bool {
+        let (first, second) = if self.arrives <= other.arrives {
+            (self, other)
+        } else {
+            (other, self)
+        };
+
+        let single_jump = first.earliest_jump.max(second.earliest_jump)
+            <= first.latest_jump.min(second.latest_jump);
+        let two_jumps = first.earliest_jump + airtime + SAFETY_MARGIN <= second.latest_jump;
+
+        single_jump || two_jumps
+    }
+
+    // Seconds to hold this obstacle back for the player to land after clearing `other`.
+    fn delay_after(&self, other: &Crossing, airtime: f32) -> f32 {
+        other.earliest_jump + airtime + SAFETY_MARGIN - self.latest_jump
+    }
+}
+
+// Spawners tick obstacle tiers independently, so two obstacles can come too close together to be
+// jumped over. Checks each new obstacle against those still ahead of the player, pushes it back
+// until it can be cleared, and drops it when it cannot be cleared at all.
+//
+// Checks are pairwise and assume full jumps at the current speed.
+#[allow(clippy::type_complexity)]
+pub fn validate_obstacle_spawns(
+    mut commands: Commands,
+    player: Single<(&Transform, &Hitbox), (With<Player>, Without<Obstacle>)>,
+    mut obstacles: Query<(Entity, Ref<Obstacle>, &mut Transform, &Hitbox, &Scrolling)>,
+    jump: Res<JumpConfig>,
+    world_scroll: Res<WorldScroll>,
+) {
+    // Nothing comes any closer while paused.
+    if world_scroll.is_paused() {
+        return;
+    }
+
+    let player = player.into_inner();
+    let airtime = jump.airtime();
+
+    let mut ahead: Vec<Crossing> = obstacles
+        .iter()
+        .filter(|(_, obstacle, _, _, _)| !obstacle.is_added())
+        .filter_map(|(_, _, transform, hitbox, scrolling)| {
+            let speed = world_scroll.speed() * scrolling.factor;
+            Crossing::new(player, (transform, hitbox), speed, &jump)
+        })
+        .filter(|crossing| crossing.leaves > 0.0)
+        .collect();
+
+    for (entity, obstacle, mut transform, hitbox, scrolling) in obstacles.iter_mut() {
+        if !obstacle.is_added() {
+            continue;
+        }
+
+        let speed = world_scroll.speed() * scrolling.factor;
+
+        let Some(mut crossing) = Crossing::new(player, (&transform, hitbox), speed, &jump) else {
+            warn_once!(
+                "Obstacles of size {} are too big to be jumped over",
+                hitbox.aabb(&transform).half_size() * 2.
+            );
+            commands.entity(entity).release();
+            continue;
+        };
+
+        // Holding an obstacle back may bring it too close to the next one, so check them all
+        // again until none conflicts.
+        for _ in 0..=ahead.len() {
+            let Some(delay) = ahead
+                .iter()
+                .find(|other| !crossing.is_passable_with(other, airtime))
+                .map(|other| crossing.delay_after(other, airtime))
+            else {
+                break;
+            };
+
+            transform.translation.x += delay * speed;
+            crossing =
+                Crossing::new(player, (&transform, hitbox), speed, &jump).unwrap_or(crossing);
+        }
+
+        if ahead
+            .iter()
+            .any(|other| !crossing.is_passable_with(other, airtime))
+        {
+            warn_once!("Dropped an obstacle that could not be placed to be jumped over");
+            commands.entity(entity).release();
+            continue;
+        }
+
+        ahead.push(crossing);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEED: f32 = 100.0;
+    // Full jumps 61.25 pixels high, in 0.875 seconds.
+    const SCREEN_UNIT: f32 = 4.0;
+    const PLAYER_SIZE: f32 = 10.0;
+
+    // Square player standing at the origin, feet at -5.
+    fn player() -> (Transform, Hitbox) {
+        (Transform::default(), Hitbox::new(Vec2::splat(PLAYER_SIZE)))
+    }
+
+    // Obstacle standing on the same ground as the player, `x` pixels ahead.
+    fn crossing(x: f32, width: f32, height: f32) -> Option<Crossing> {
+        let (player_transform, player_hitbox) = player();
+        let transform = Transform::from_xyz(x, (height - PLAYER_SIZE) / 2.0, 0.0);
+
+        Crossing::new(
+            (&player_transform, &player_hitbox),
+            (&transform, &Hitbox::new(Vec2::new(width, height))),
+            SPEED,
+            &JumpConfig::new(SCREEN_UNIT),
+        )
+    }
+
+    fn airtime() -> f32 {
+        JumpConfig::new(SCREEN_UNIT).airtime()
+    }
+
+    #[test]
+    fn crossings_span_the_obstacle() {
+        let crossing = crossing(100.0, 10.0, 20.0).unwrap();
+
+        // Half widths of both added on each side.
+        assert!((crossing.arrives - 0.9).abs() < 1e-5);
+        assert!((crossing.leaves - 1.1).abs() < 1e-5);
+        assert!(crossing.earliest_jump < crossing.arrives);
+        assert!(crossing.earliest_jump <= crossing.latest_jump);
+    }
+
+    #[test]
+    fn unreachable_heights_have_no_crossing() {
+        let max_height = JumpConfig::new(SCREEN_UNIT).max_height();
+
+        assert!(crossing(100.0, 10.0, max_height - 10.0).is_some());
+        assert!(crossing(100.0, 10.0, max_height + 1.0).is_none());
+        // Reachable, but only for a moment at the top of the jump.
+        assert!(crossing(100.0, 10.0, max_height - 1.0).is_none());
+        // Low enough, but too wide to stay above it for the whole crossing.
+        assert!(crossing(100.0, 200.0, 20.0).is_none());
+    }
+
+    #[test]
+    fn close_obstacles_pass_with_a_single_jump() {
+        let first = crossing(100.0, 10.0, 20.0).unwrap();
+        let second = crossing(115.0, 10.0, 20.0).unwrap();
+
+        assert!(first.is_passable_with(&second, airtime()));
+        assert!(second.is_passable_with(&first, airtime()));
+    }
+
+    #[test]
+    fn far_obstacles_pass_with_two_jumps() {
+        let first = crossing(100.0, 10.0, 50.0).unwrap();
+        let second = crossing(300.0, 10.0, 50.0).unwrap();
+
+        // Too far apart for a single jump.
+        assert!(first.leaves - second.arrives < -airtime());
+        assert!(first.is_passable_with(&second, airtime()));
+    }
+
+    #[test]
+    fn delay_makes_conflicting_obstacles_passable() {
+        let first = crossing(100.0, 10.0, 50.0).unwrap();
+        let second = crossing(140.0, 10.0, 50.0).unwrap();
+        assert!(!first.is_passable_with(&second, airtime()));
+
+        let delay = second.delay_after(&first, airtime());
+        assert!(delay > 0.0);
+
+        let delayed = crossing(140.0 + delay * SPEED, 10.0, 50.0).unwrap();
+        assert!(first.is_passable_with(&delayed, airtime()));
+        assert!((delayed.delay_after(&first, airtime())).abs() < 1e-4);
+    }
+}
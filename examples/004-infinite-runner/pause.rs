@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+
+use bevy_game_bits::cleanup::{CleanupPlugin, DespawnOnExit};
+
+use crate::game_state::{GameStates, PlayStates, RestartRequested};
+
+const PAUSE_KEYS: [KeyCode; 2] = [KeyCode::Escape, KeyCode::KeyP];
+const UP_KEYS: [KeyCode; 2] = [KeyCode::ArrowUp, KeyCode::KeyW];
+const DOWN_KEYS: [KeyCode; 2] = [KeyCode::ArrowDown, KeyCode::KeyS];
+// Not Space, which would also jump as soon as the game resumes.
+const CONFIRM_KEYS: [KeyCode; 2] = [KeyCode::Enter, KeyCode::NumpadEnter];
+
+const OVERLAY_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.75);
+const SELECTED_COLOR: Color = Color::WHITE;
+const IDLE_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+const MENU_FONT_SIZE: f32 = 32.0;
+
+// Freezes the run: the virtual clock stops, so fixed systems, jump timing and spawner cooldowns
+// all stay where they were, and so do recordings.
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(CleanupPlugin::<PlayStates>::default())
+            .add_systems(
+                OnEnter(PlayStates::Paused),
+                (pause_virtual_time, spawn_pause_menu),
+            )
+            .add_systems(OnExit(PlayStates::Paused), unpause_virtual_time)
+            .add_systems(Update, toggle_pause.run_if(in_state(GameStates::Play)))
+            .add_systems(
+                Update,
+                (navigate_pause_menu, highlight_pause_menu)
+                    .chain()
+                    .run_if(in_state(PlayStates::Paused)),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PauseMenuItem {
+    Resume,
+    Restart,
+    Quit,
+}
+
+impl PauseMenuItem {
+    const ALL: [PauseMenuItem; 3] = [
+        PauseMenuItem::Resume,
+        PauseMenuItem::Restart,
+        PauseMenuItem::Quit,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            PauseMenuItem::Resume => "Resume",
+            PauseMenuItem::Restart => "Restart",
+            PauseMenuItem::Quit => "Quit",
+        }
+    }
+}
+
+// Holds the index of the selected item.
+#[derive(Component)]
+struct PauseMenu(usize);
+
+#[derive(Component)]
+struct PauseMenuEntry(usize);
+
+fn just_pressed(
+    keyboard: &ButtonInput<KeyCode>,
+    gamepads: &Query<&Gamepad>,
+    keys: &[KeyCode],
+    buttons: &[GamepadButton],
+) -> bool {
+    keyboard.any_just_pressed(keys.iter().copied())
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.any_just_pressed(buttons.iter().copied()))
+}
+
+fn pause_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+// Also runs when leaving the run from the menu.
+fn unpause_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn toggle_pause(
+    state: Res<State<PlayStates>>,
+    mut next_state: ResMut<NextState<PlayStates>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+) {
+    if !just_pressed(&keyboard, &gamepads, &PAUSE_KEYS, &[GamepadButton::Start]) {
+        return;
+    }
+
+    next_state.set(match state.get() {
+        PlayStates::Running => PlayStates::Paused,
+        PlayStates::Paused => PlayStates::Running,
+    });
+}
+
+fn spawn_pause_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            PauseMenu(0),
+            DespawnOnExit::new(PlayStates::Paused),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(OVERLAY_COLOR),
+            // Above the virtual jump button.
+            GlobalZIndex(1),
+        ))
+        .with_children(|parent| {
+            parent.spawn((Text::new("PAUSED"), TextFont::from_font_size(48.0)));
+
+            for (index, item) in PauseMenuItem::ALL.iter().enumerate() {
+                parent.spawn((
+                    PauseMenuEntry(index),
+                    Text::new(item.label()),
+                    TextFont::from_font_size(MENU_FONT_SIZE),
+                    TextColor(IDLE_COLOR),
+                ));
+            }
+        });
+}
+
+fn navigate_pause_menu(
+    mut commands: Commands,
+    menu: Single<&mut PauseMenu>,
+    mut next_play_state: ResMut<NextState<PlayStates>>,
+    mut next_game_state: ResMut<NextState<GameStates>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+) {
+    let mut menu = menu.into_inner();
+    let count = PauseMenuItem::ALL.len();
+
+    if just_pressed(&keyboard, &gamepads, &UP_KEYS, &[GamepadButton::DPadUp]) {
+        menu.0 = (menu.0 + count - 1) % count;
+    }
+
+    if just_pressed(&keyboard, &gamepads, &DOWN_KEYS, &[GamepadButton::DPadDown]) {
+        menu.0 = (menu.0 + 1) % count;
+    }
+
+    if just_pressed(&keyboard, &gamepads, &[], &[GamepadButton::East]) {
+        next_play_state.set(PlayStates::Running);
+        return;
+    }
+
+    if !just_pressed(&keyboard, &gamepads, &CONFIRM_KEYS, &[GamepadButton::South]) {
+        return;
+    }
+
+    match PauseMenuItem::ALL[menu.0] {
+        PauseMenuItem::Resume => next_play_state.set(PlayStates::Running),
+        PauseMenuItem::Restart => {
+            // Through the title screen, as re-entering the same state runs no transitions.
+            commands.insert_resource(RestartRequested);
+            next_game_state.set(GameStates::InsertCoin);
+        }
+        PauseMenuItem::Quit => next_game_state.set(GameStates::InsertCoin),
+    }
+}
+
+fn highlight_pause_menu(
+    menus: Query<&PauseMenu, Changed<PauseMenu>>,
+    mut entries: Query<(&PauseMenuEntry, &mut TextColor)>,
+) {
+    let Ok(menu) = menus.get_single() else {
+        return;
+    };
+
+    for (entry, mut color) in entries.iter_mut() {
+        color.0 = if entry.0 == menu.0 {
+            SELECTED_COLOR
+        } else {
+            IDLE_COLOR
+        };
+    }
+}
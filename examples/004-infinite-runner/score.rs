@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+
+use bevy_game_bits::cleanup::DespawnOnExit;
+use bevy_game_bits::collision::Hitbox;
+use bevy_game_bits::health::Damaged;
+use bevy_game_bits::scoring::{ScoreChanged, ScoreSource, Scoring};
+use bevy_game_bits::scroll::WorldScroll;
+
+use crate::actors::{Obstacle, Player};
+use crate::game_state::GameStates;
+
+// Pixels between the player's feet and the top of an obstacle for a near miss.
+const NEAR_MISS_MARGIN: f32 = 12.0;
+
+const POPUP_FONT_SIZE: f32 = 16.0;
+const POPUP_LIFETIME: f32 = 0.75;
+// Pixels per second.
+const POPUP_RISE_SPEED: f32 = 40.0;
+
+// How close the player came to an obstacle while jumping over it.
+#[derive(Component)]
+pub struct Clearance {
+    closest: f32,
+    passed: bool,
+}
+
+impl Default for Clearance {
+    fn default() -> Self {
+        Self {
+            closest: f32::INFINITY,
+            passed: false,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ScorePopup {
+    age: f32,
+}
+
+pub fn score_distance(
+    mut scoring: ResMut<Scoring>,
+    world_scroll: Res<WorldScroll>,
+    time: Res<Time>,
+) {
+    scoring.travel(world_scroll.speed() * time.delta_secs());
+}
+
+// Obstacles count as cleared once fully behind the player.
+pub fn score_passed_obstacles(
+    player: Single<(&Transform, &Hitbox), With<Player>>,
+    mut obstacles: Query<(&Transform, &Hitbox, &mut Clearance), With<Obstacle>>,
+    mut scoring: ResMut<Scoring>,
+) {
+    let (player_transform, player_hitbox) = player.into_inner();
+    let player_box = player_hitbox.aabb(player_transform);
+
+    for (transform, hitbox, mut clearance) in obstacles.iter_mut() {
+        if clearance.passed {
+            continue;
+        }
+
+        let obstacle_box = hitbox.aabb(transform);
+
+        if obstacle_box.max.x < player_box.min.x {
+            clearance.passed = true;
+
+            // Ran into it rather than over it.
+            if clearance.closest < 0.0 {
+                continue;
+            }
+
+            scoring.clear();
+
+            if clearance.closest < NEAR_MISS_MARGIN {
+                scoring.near_miss();
+            }
+        } else if obstacle_box.min.x <= player_box.max.x {
+            // Obstacles are only ever jumped over.
+            clearance.closest = clearance.closest.min(player_box.min.y - obstacle_box.max.y);
+        }
+    }
+}
+
+pub fn break_streak(
+    mut events: EventReader<Damaged>,
+    player: Single<Entity, With<Player>>,
+    mut scoring: ResMut<Scoring>,
+) {
+    if events.read().any(|event| event.entity == *player) {
+        scoring.hit();
+    }
+}
+
+pub fn spawn_score_popups(
+    mut commands: Commands,
+    mut events: EventReader<ScoreChanged>,
+    player: Single<&Transform, With<Player>>,
+) {
+    // Bonuses only, distance adds up every tick.
+    let bonuses = events
+        .read()
+        .filter(|event| event.source != ScoreSource::Distance);
+
+    for (index, event) in bonuses.enumerate() {
+        let label = match (event.source, event.multiplier) {
+            (ScoreSource::NearMiss, _) => format!("NEAR MISS +{}", event.points),
+            (_, 1) => format!("+{}", event.points),
+            (_, multiplier) => format!("+{} x{}", event.points, multiplier),
+        };
+
+        commands.spawn((
+            ScorePopup { age: 0.0 },
+            DespawnOnExit::new(GameStates::Play),
+            Text2d::new(label),
+            TextFont::from_font_size(POPUP_FONT_SIZE),
+            TextColor(Color::WHITE),
+            // Stacked when several show up at once.
+            Transform::from_translation(
+                player.translation + Vec3::new(0.0, 40.0 + index as f32 * POPUP_FONT_SIZE, 2.0),
+            ),
+        ));
+    }
+}
+
+pub fn animate_score_popups(
+    mut commands: Commands,
+    mut popups: Query<(Entity, &mut ScorePopup, &mut Transform, &mut TextColor)>,
+    time: Res<Time>,
+) {
+    for (entity, mut popup, mut transform, mut color) in popups.iter_mut() {
+        popup.age += time.delta_secs();
+
+        if popup.age >= POPUP_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += POPUP_RISE_SPEED * time.delta_secs();
+        color.0.set_alpha(1.0 - popup.age / POPUP_LIFETIME);
+    }
+}
@@ -1,11 +1,14 @@
 use bevy::prelude::*;
 
+use bevy_game_bits::health::Health;
+use bevy_game_bits::rng::GameRng;
+use bevy_game_bits::scoring::Scoring;
+
+use crate::actors::Player;
+
 pub const SCOREBOARD_TEXT_PADDING: Val = Val::Px(4.0);
 pub const SCOREBOARD_FONT_SIZE: f32 = 48.0;
 
-#[derive(Resource)]
-pub struct Score(pub u32);
-
 #[derive(Component)]
 pub struct InstructionsText;
 
@@ -15,6 +18,9 @@ pub struct TitleText;
 #[derive(Component)]
 pub struct ScoreText;
 
+#[derive(Component)]
+pub struct LivesText;
+
 #[derive(Component)]
 pub struct GameOverText;
 
@@ -24,12 +30,31 @@ pub struct HitSpaceText;
 #[derive(Resource)]
 pub struct WindowSize(pub f32, pub f32);
 
-pub fn update_score_text(score_text: Single<&mut Text, With<ScoreText>>, score: Res<Score>) {
+pub fn update_score_text(score_text: Single<&mut Text, With<ScoreText>>, scoring: Res<Scoring>) {
     let mut text = score_text.into_inner();
-    text.0 = score.0.to_string();
+    text.0 = scoring.total().to_string();
+}
+
+// The score of a run left through the pause menu, which goes back to the title screen.
+pub fn despawn_score_text(mut commands: Commands, score_text: Query<Entity, With<ScoreText>>) {
+    for entity in score_text.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn update_lives_text(
+    lives_text: Single<&mut Text, With<LivesText>>,
+    player: Single<&Health, With<Player>>,
+) {
+    let mut text = lives_text.into_inner();
+    let lives = format!("Lives {}", player.current());
+
+    if text.0 != lives {
+        text.0 = lives;
+    }
 }
 
-pub fn display_game_over_text(mut commands: Commands) {
+pub fn display_game_over_text(mut commands: Commands, rng: Res<GameRng>) {
     commands.spawn((
         Text2d::new("GAME OVER"),
         TextLayout::new_with_justify(JustifyText::Center),
@@ -39,6 +64,15 @@ pub fn display_game_over_text(mut commands: Commands) {
         Transform::from_xyz(0.0, 0.0 + 48.0 + 16.0, 0.0),
     ));
 
+    // Play it again with `-- --seed <seed>`.
+    commands.spawn((
+        Text2d::new(format!("Seed {}", rng.seed())),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont::from_font_size(16.0),
+        InstructionsText,
+        Transform::from_xyz(0.0, 0.0 - 32.0, 0.0),
+    ));
+
     add_instructions_text(&mut commands)
 }
 
@@ -1,19 +1,39 @@
 // Features a small Vampire Survivor-like movement and experience points grab.
-// Hit Space to spawn XP Points.
+// Hit Space to spawn XP Points. Double-tap an arrow to dash.
 
 use bevy::math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume};
 use bevy::prelude::*;
 
+use bevy_game_bits::actions::Action;
+use bevy_game_bits::collision::{CollisionLayers, CollisionPlugin, CollisionStarted, Hitbox};
+use bevy_game_bits::combo::{Combo, ComboCompleted, ComboPlugin, Combos};
+use bevy_game_bits::movement::{TopDownMovement, TopDownMovementPlugin};
+use bevy_game_bits::pool::ReleaseExt;
+use bevy_game_bits::spawner::{SpawnPosition, SpawnTier, Spawner, SpawnerPlugin};
+use bevy_game_bits::virtual_controls::{spawn_virtual_joystick, VirtualControlsPlugin};
+
 const GOLD: Srgba = bevy::color::palettes::css::GOLD;
 const GREEN: Srgba = bevy::color::palettes::css::GREEN;
 const WHITE: Srgba = bevy::color::palettes::css::BLACK;
 
 const PLAYER_SPEED: f32 = 100.0;
+const DASH_DISTANCE: f32 = 64.0;
+const DOUBLE_TAP_WINDOW: f32 = 0.25;
+
+const JOYSTICK_SIZE: Val = Val::Px(128.0);
+const JOYSTICK_MARGIN: Val = Val::Px(24.0);
+const JOYSTICK_DEADZONE: f32 = 0.3;
 const XP_SPEED: f32 = 20.0;
+// Keeps XP from spawning under the window edges.
+const XP_MARGIN: f32 = 16.0;
 
 const PLAYER_SIZE: f32 = 32.0;
 const COLLIDER_SIZE: f32 = 96.0;
 
+// Collision layers, so that XP is not tested against XP.
+const PLAYER_LAYER: u32 = 1 << 0;
+const XP_LAYER: u32 = 1 << 1;
+
 #[derive(Component)]
 struct XP;
 
@@ -29,23 +49,23 @@ struct Experience(u128);
 #[derive(Component)]
 struct XpGauge;
 
-#[derive(Event)]
-struct CollisionEvent;
-
 fn main() {
     App::new()
-        .add_event::<CollisionEvent>()
         .insert_resource(Experience(0))
         .add_plugins(DefaultPlugins)
+        .add_plugins(ComboPlugin)
+        .add_plugins(VirtualControlsPlugin)
+        .add_plugins(TopDownMovementPlugin)
+        .add_plugins(SpawnerPlugin)
+        .add_plugins(CollisionPlugin)
         .add_systems(Startup, setup)
         .add_systems(
             Update,
             (
-                move_player,
+                dash_player,
                 maybe_spawn_xp,
                 detect_xp_collider_collision,
-                detect_xp_player_collision,
-                maybe_increase_score,
+                grab_xp,
             ),
         )
         .run();
@@ -55,9 +75,49 @@ fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    mut combos: ResMut<Combos>,
     window_q: Single<&Window>,
 ) {
     let window: &Window = window_q.into_inner();
+    let xp_area = Vec2::new(window.width(), window.height()) / 2.0 - XP_MARGIN;
+
+    // XP is only spawned on demand, see `maybe_spawn_xp`, somewhere in the window.
+    let xp_mesh = meshes.add(Rectangle::new(4.0, 4.0));
+    let xp_material = materials.add(Color::from(GOLD));
+    commands.spawn(
+        Spawner::new(vec![SpawnTier::new(
+            "xp",
+            f32::INFINITY,
+            move |entity, _context| {
+                entity.insert((
+                    XP,
+                    Hitbox::new(Vec2::splat(4.0)),
+                    CollisionLayers::new(XP_LAYER, PLAYER_LAYER),
+                    Mesh2d(xp_mesh.clone()),
+                    MeshMaterial2d(xp_material.clone()),
+                ));
+            },
+        )
+        .with_pool("xp")
+        .at(SpawnPosition::Area {
+            min: (-xp_area).extend(1.0),
+            max: xp_area.extend(1.0),
+        })])
+        .with_stream("loot"),
+    );
+
+    for (name, action) in [
+        ("dash-left", Action::Left),
+        ("dash-up", Action::Up),
+        ("dash-right", Action::Right),
+        ("dash-down", Action::Down),
+    ] {
+        combos.add(
+            Combo::new(name)
+                .then(action, 0.0)
+                .then(action, DOUBLE_TAP_WINDOW),
+        );
+    }
 
     commands.spawn(Camera2d);
 
@@ -74,14 +134,32 @@ fn setup(
         Transform::from_xyz(0.0, 0.0 + 48.0 + 16.0, 0.0),
     ));
 
+    // On-screen joystick, for touch screens.
+    spawn_virtual_joystick(
+        &mut commands,
+        JOYSTICK_DEADZONE,
+        Node {
+            position_type: PositionType::Absolute,
+            width: JOYSTICK_SIZE,
+            height: JOYSTICK_SIZE,
+            left: JOYSTICK_MARGIN,
+            bottom: JOYSTICK_MARGIN,
+            ..default()
+        },
+    );
+
     commands
         .spawn((
             Player,
+            TopDownMovement::new(PLAYER_SPEED).with_response(EaseFunction::QuadraticIn),
             Transform {
                 translation: Vec3::new(0.0, 0.0, 0.0),
                 scale: Vec3::new(PLAYER_SIZE, PLAYER_SIZE, 1.0),
                 ..default()
             },
+            // Drawn scaled up, which hitboxes ignore.
+            Hitbox::new(Vec2::splat(PLAYER_SIZE)),
+            CollisionLayers::new(PLAYER_LAYER, XP_LAYER),
             Mesh2d(meshes.add(Rectangle::new(1.0, 1.0))),
             MeshMaterial2d(materials.add(Color::from(WHITE))),
         ))
@@ -98,56 +176,31 @@ fn setup(
         });
 }
 
-fn move_player(
+fn dash_player(
     player_q: Single<&mut Transform, With<Player>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    time: Res<Time>,
+    mut events: EventReader<ComboCompleted>,
 ) {
     let mut player = player_q.into_inner();
 
-    let elapsed: f32 = time.delta_secs();
-
-    if keyboard.pressed(KeyCode::ArrowLeft) {
-        player.translation.x -= PLAYER_SPEED * elapsed;
-    }
-
-    if keyboard.pressed(KeyCode::ArrowUp) {
-        player.translation.y += PLAYER_SPEED * elapsed;
-    }
+    for event in events.read() {
+        let direction = match event.name.as_str() {
+            "dash-left" => Vec3::NEG_X,
+            "dash-up" => Vec3::Y,
+            "dash-right" => Vec3::X,
+            "dash-down" => Vec3::NEG_Y,
+            _ => continue,
+        };
 
-    if keyboard.pressed(KeyCode::ArrowRight) {
-        player.translation.x += PLAYER_SPEED * elapsed;
-    }
-
-    if keyboard.pressed(KeyCode::ArrowDown) {
-        player.translation.y -= PLAYER_SPEED * elapsed;
+        player.translation += direction * DASH_DISTANCE;
     }
 }
 
-fn maybe_spawn_xp(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-) {
+fn maybe_spawn_xp(spawner_q: Single<&mut Spawner>, keyboard: Res<ButtonInput<KeyCode>>) {
     if keyboard.just_pressed(KeyCode::Space) {
-        do_spawn_xp(&mut commands, &mut meshes, &mut materials);
+        spawner_q.into_inner().spawn_now("xp");
     }
 }
 
-fn do_spawn_xp(
-    commands: &mut Commands,
-    meshes: &mut Assets<Mesh>,
-    materials: &mut Assets<ColorMaterial>,
-) {
-    commands.spawn((
-        XP,
-        Transform::from_xyz(-100.0, 0.0, 1.0),
-        Mesh2d(meshes.add(Rectangle::new(4.0, 4.0))),
-        MeshMaterial2d(materials.add(Color::from(GOLD))),
-    ));
-}
-
 fn detect_xp_collider_collision(
     collider_q: Single<&PlayerCollider>,
     player_q: Single<&Transform, With<Player>>,
@@ -190,39 +243,15 @@ fn do_detect_cllision(bounding_circle: BoundingCircle, aabb: Aabb2d) -> bool {
     return false;
 }
 
-fn detect_xp_player_collision(
+fn grab_xp(
     mut commands: Commands,
-    player_q: Single<&Transform, With<Player>>,
-    xp_q: Query<(Entity, &Transform), With<XP>>,
-    mut events: EventWriter<CollisionEvent>,
-) {
-    let player_t = player_q.into_inner();
-
-    let player_aabb = Aabb2d::new(
-        player_t.translation.truncate(),
-        player_t.scale.truncate() / 2.0,
-    );
-
-    for (xp_entity, xp_t) in xp_q.iter() {
-        let xp_aabb = Aabb2d::new(xp_t.translation.truncate(), xp_t.scale.truncate() / 2.0);
-
-        if player_aabb.intersects(&xp_aabb.bounding_circle()) {
-            events.send(CollisionEvent);
-
-            commands.entity(xp_entity).despawn();
-        }
-    }
-}
-
-fn maybe_increase_score(
     mut experience: ResMut<Experience>,
     gauge_q: Single<&mut Node, With<XpGauge>>,
-    mut events: EventReader<CollisionEvent>,
+    player_q: Single<Entity, With<Player>>,
+    xp_q: Query<(), With<XP>>,
+    mut events: EventReader<CollisionStarted>,
 ) {
-    if events.is_empty() {
-        return;
-    }
-
+    let player = player_q.into_inner();
     let mut gauge_node = gauge_q.into_inner();
 
     let max_width: f32 = match gauge_node.max_width {
@@ -230,8 +259,115 @@ fn maybe_increase_score(
         _ => 0.0,
     };
 
-    for _event in events.read() {
+    for CollisionStarted(contact) in events.read() {
+        let Some(xp_entity) = contact.other(player).filter(|&other| xp_q.contains(other)) else {
+            continue;
+        };
+
+        commands.entity(xp_entity).release();
+
         experience.0 += 100;
         gauge_node.width = Val::Px(experience.0 as f32 / 1000.0) * max_width;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::time::Duration;
+
+    use bevy::asset::AssetPlugin;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    const FRAME_TIME: Duration = Duration::from_nanos(16_666_667);
+    const GRABS: usize = 50;
+    // Enough for a fixed tick to spawn XP, or to test collisions.
+    const FRAMES_PER_TICK: usize = 3;
+
+    // Every XP entity ever spawned, which only grows when the pool has none to give back.
+    #[derive(Resource, Default)]
+    struct SpawnedXp(HashSet<Entity>);
+
+    #[derive(Debug, PartialEq)]
+    struct Counts {
+        entities: u32,
+        spawned_xp: usize,
+        meshes: usize,
+        materials: usize,
+    }
+
+    fn track_xp(mut spawned: ResMut<SpawnedXp>, xp: Query<Entity, Added<XP>>) {
+        spawned.0.extend(xp.iter());
+    }
+
+    // The game without rendering nor movement, keys pressed by hand.
+    fn app() -> App {
+        let mut app = App::new();
+        app.insert_resource(Experience(0))
+            .add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<SpawnedXp>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+            .add_plugins(ComboPlugin)
+            .add_plugins(SpawnerPlugin)
+            .add_plugins(CollisionPlugin)
+            .add_systems(Startup, setup)
+            .add_systems(Update, (maybe_spawn_xp, track_xp, grab_xp).chain());
+
+        app.world_mut().spawn(Window::default());
+        app.finish();
+        app.cleanup();
+        app
+    }
+
+    // Spawns XP with Space, then drops it on the player.
+    fn grab(app: &mut App) -> Counts {
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        app.update();
+
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release(KeyCode::Space);
+        keyboard.clear();
+        for _ in 0..FRAMES_PER_TICK {
+            app.update();
+        }
+
+        let mut xp = app
+            .world_mut()
+            .query_filtered::<&mut Transform, (With<XP>, With<Hitbox>)>();
+        for mut transform in xp.iter_mut(app.world_mut()) {
+            transform.translation = Vec3::new(0.0, 0.0, 1.0);
+        }
+
+        for _ in 0..FRAMES_PER_TICK {
+            app.update();
+        }
+
+        let world = app.world();
+        Counts {
+            entities: world.entities().len(),
+            spawned_xp: world.resource::<SpawnedXp>().0.len(),
+            meshes: world.resource::<Assets<Mesh>>().len(),
+            materials: world.resource::<Assets<ColorMaterial>>().len(),
+        }
+    }
+
+    #[test]
+    fn grabbed_xp_is_reused() {
+        let mut app = app();
+        let grabs: Vec<Counts> = (0..GRABS).map(|_| grab(&mut app)).collect();
+
+        assert_eq!(app.world().resource::<Experience>().0, GRABS as u128 * 100);
+        // A single XP entity goes back and forth between the pool and the window.
+        assert_eq!(grabs[0].spawned_xp, 1);
+        for counts in grabs.iter() {
+            assert_eq!(*counts, grabs[0], "grabs: {:?}", grabs);
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+// Bit mask of the actions held during one fixed tick, see `Action::bit`.
+pub type ActionMask = u16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Jump,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Action {
+    pub const ALL: [Action; 5] = [
+        Action::Jump,
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+    ];
+
+    pub fn bit(self) -> ActionMask {
+        1 << self as u16
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ActionsSet {
+    // Input sources press actions for the upcoming tick.
+    Collect,
+    // Replays may overwrite whatever was collected.
+    Override,
+    // The collected mask becomes the state seen by `FixedUpdate`.
+    Commit,
+}
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Actions>()
+            .init_resource::<ActionBindings>()
+            .configure_sets(
+                FixedPreUpdate,
+                (
+                    ActionsSet::Collect,
+                    ActionsSet::Override,
+                    ActionsSet::Commit,
+                )
+                    .chain(),
+            )
+            .add_systems(PreUpdate, latch_keyboard_actions.after(InputSystem))
+            .add_systems(
+                FixedPreUpdate,
+                (
+                    collect_keyboard_actions.in_set(ActionsSet::Collect),
+                    commit_actions.in_set(ActionsSet::Commit),
+                ),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct ActionBindings {
+    pub keys: Vec<(KeyCode, Action)>,
+}
+
+impl Default for ActionBindings {
+    fn default() -> Self {
+        Self {
+            keys: vec![
+                (KeyCode::Space, Action::Jump),
+                (KeyCode::ArrowUp, Action::Up),
+                (KeyCode::ArrowDown, Action::Down),
+                (KeyCode::ArrowLeft, Action::Left),
+                (KeyCode::ArrowRight, Action::Right),
+            ],
+        }
+    }
+}
+
+// Action state sampled once per fixed tick, so that systems running in `FixedUpdate` see each
+// press exactly once, whatever the frame rate is.
+#[derive(Resource, Default, Debug)]
+pub struct Actions {
+    // Presses seen by frames that ran no fixed tick, for the next tick to pick up.
+    latched: ActionMask,
+    incoming: ActionMask,
+    current: ActionMask,
+    previous: ActionMask,
+}
+
+impl Actions {
+    pub fn press(&mut self, action: Action) {
+        self.incoming |= action.bit();
+    }
+
+    // Pressed on the next tick, even when released by then.
+    pub fn latch(&mut self, action: Action) {
+        self.latched |= action.bit();
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.current & action.bit() != 0
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed(action) && self.previous & action.bit() == 0
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        !self.pressed(action) && self.previous & action.bit() != 0
+    }
+
+    pub fn mask(&self) -> ActionMask {
+        self.current
+    }
+
+    pub fn incoming_mask(&self) -> ActionMask {
+        self.incoming
+    }
+
+    pub fn set_incoming_mask(&mut self, mask: ActionMask) {
+        self.incoming = mask;
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+// Every frame, as frames may run no fixed tick at all: a tap shorter than a tick is still seen
+// as a press.
+pub fn latch_keyboard_actions(
+    mut actions: ResMut<Actions>,
+    bindings: Res<ActionBindings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+) {
+    for (key_code, action) in bindings.keys.iter() {
+        if keyboard.just_pressed(*key_code) {
+            actions.latch(*action);
+        }
+    }
+}
+
+pub fn collect_keyboard_actions(
+    mut actions: ResMut<Actions>,
+    bindings: Res<ActionBindings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+) {
+    actions.incoming |= std::mem::take(&mut actions.latched);
+
+    for (key_code, action) in bindings.keys.iter() {
+        if keyboard.pressed(*key_code) {
+            actions.press(*action);
+        }
+    }
+}
+
+pub fn commit_actions(mut actions: ResMut<Actions>) {
+    actions.previous = actions.current;
+    actions.current = actions.incoming;
+    actions.incoming = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Actions>();
+        world.init_resource::<ActionBindings>();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world
+    }
+
+    fn frame(world: &mut World, ticks: usize) {
+        world.run_system_once(latch_keyboard_actions).unwrap();
+
+        for _ in 0..ticks {
+            world.run_system_once(collect_keyboard_actions).unwrap();
+            world.run_system_once(commit_actions).unwrap();
+        }
+
+        world.resource_mut::<ButtonInput<KeyCode>>().clear();
+    }
+
+    #[test]
+    fn tap_during_a_frame_without_ticks_is_seen() {
+        let mut world = world();
+
+        let mut keyboard = world.resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.press(KeyCode::Space);
+        keyboard.release(KeyCode::Space);
+        frame(&mut world, 0);
+        frame(&mut world, 1);
+
+        let actions = world.resource::<Actions>();
+        assert!(actions.just_pressed(Action::Jump));
+
+        frame(&mut world, 1);
+        assert!(!world.resource::<Actions>().pressed(Action::Jump));
+    }
+
+    #[test]
+    fn held_key_is_pressed_once() {
+        let mut world = world();
+
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        frame(&mut world, 2);
+
+        let actions = world.resource::<Actions>();
+        assert!(actions.pressed(Action::Jump));
+        assert!(!actions.just_pressed(Action::Jump));
+
+        world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .release(KeyCode::Space);
+        frame(&mut world, 1);
+
+        assert!(world.resource::<Actions>().just_released(Action::Jump));
+    }
+}
@@ -0,0 +1,792 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::time::Duration;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext, RenderAssetUsages};
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use flate2::read::ZlibDecoder;
+
+use crate::sprite_animation::{switch_jump_animations, SpriteAnimation};
+
+// Loads `.aseprite` and `.ase` files straight into a texture atlas, one tile per frame, along
+// with frame durations and tags. Visible layers are flattened with normal blending; tilemap
+// layers and blend modes are not supported.
+//
+// The atlas image and layout are also available as the `image` and `layout` labeled assets.
+pub struct AsepritePlugin;
+
+impl Plugin for AsepritePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Aseprite>()
+            .register_asset_loader(AsepriteLoader)
+            .add_systems(
+                Update,
+                apply_aseprite_animations.before(switch_jump_animations),
+            );
+    }
+}
+
+#[derive(Asset, TypePath, Debug)]
+pub struct Aseprite {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub frame_size: UVec2,
+    // One per frame, which is also the atlas index.
+    pub frame_durations: Vec<Duration>,
+    pub tags: HashMap<String, AsepriteTag>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsepriteDirection {
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+#[derive(Debug, Clone)]
+pub struct AsepriteTag {
+    // First and last frames, both included.
+    pub from: usize,
+    pub to: usize,
+    pub direction: AsepriteDirection,
+    // How many times the tag plays, 0 for forever.
+    pub repeat: u16,
+}
+
+// Plays the tags of an Aseprite file on the sprite of the same entity. Its `SpriteAnimation`
+// gets one clip per tag once the file is loaded, and again whenever the file changes.
+#[derive(Component, Debug)]
+#[require(SpriteAnimation)]
+pub struct AsepriteAnimation {
+    pub aseprite: Handle<Aseprite>,
+    // Clip, position in the clip and name, see `SpriteClip::with_event`.
+    events: Vec<(String, usize, String)>,
+    applied: bool,
+}
+
+impl AsepriteAnimation {
+    pub fn new(aseprite: Handle<Aseprite>) -> Self {
+        Self {
+            aseprite,
+            events: Vec::new(),
+            applied: false,
+        }
+    }
+
+    pub fn with_event(mut self, clip: &str, position: usize, name: &str) -> Self {
+        self.events
+            .push((clip.to_string(), position, name.to_string()));
+        self
+    }
+}
+
+pub fn apply_aseprite_animations(
+    mut query: Query<(&mut AsepriteAnimation, &mut SpriteAnimation, &mut Sprite)>,
+    mut events: EventReader<AssetEvent<Aseprite>>,
+    aseprites: Res<Assets<Aseprite>>,
+) {
+    let modified: Vec<AssetId<Aseprite>> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (mut source, mut animation, mut sprite) in query.iter_mut() {
+        if source.applied && !modified.contains(&source.aseprite.id()) {
+            continue;
+        }
+
+        let Some(aseprite) = aseprites.get(&source.aseprite) else {
+            continue;
+        };
+
+        let mut clips = SpriteAnimation::from_aseprite(aseprite);
+        for (clip, position, name) in source.events.iter() {
+            clips.add_event(clip, *position, name);
+        }
+        animation.replace_clips(clips);
+
+        sprite.image = aseprite.image.clone();
+        // The file may have lost frames.
+        let index = sprite
+            .texture_atlas
+            .as_ref()
+            .map_or(0, |atlas| atlas.index)
+            .min(aseprite.frame_durations.len() - 1);
+        sprite.texture_atlas = Some(TextureAtlas {
+            layout: aseprite.layout.clone(),
+            index,
+        });
+
+        source.applied = true;
+    }
+}
+
+#[derive(Default)]
+pub struct AsepriteLoader;
+
+#[derive(Debug)]
+pub enum AsepriteError {
+    Io(std::io::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for AsepriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsepriteError::Io(error) => write!(f, "could not read Aseprite file: {}", error),
+            AsepriteError::Invalid(reason) => write!(f, "invalid Aseprite file: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for AsepriteError {}
+
+impl AssetLoader for AsepriteLoader {
+    type Asset = Aseprite;
+    type Settings = ();
+    type Error = AsepriteError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Aseprite, AsepriteError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(AsepriteError::Io)?;
+
+        let file = AsepriteFile::parse(&bytes)?;
+        let frame_size = UVec2::new(file.width, file.height);
+        let (pixels, columns, rows) = file.atlas_pixels();
+
+        let image = Image::new(
+            Extent3d {
+                width: file.width * columns,
+                height: file.height * rows,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            pixels,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        let layout = TextureAtlasLayout::from_grid(frame_size, columns, rows, None, None);
+
+        Ok(Aseprite {
+            image: load_context.add_labeled_asset("image".to_string(), image),
+            layout: load_context.add_labeled_asset("layout".to_string(), layout),
+            frame_size,
+            frame_durations: file.frames.iter().map(|frame| frame.duration).collect(),
+            tags: file.tags,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite", "ase"]
+    }
+}
+
+// https://github.com/aseprite/aseprite/blob/main/docs/ase-file-specs.md
+const FILE_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+const HEADER_SIZE: usize = 128;
+
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_TAGS: u16 = 0x2018;
+const CHUNK_PALETTE: u16 = 0x2019;
+
+const FLAG_LAYER_OPACITY: u32 = 1;
+const LAYER_VISIBLE: u16 = 1;
+const LAYER_REFERENCE: u16 = 64;
+const LAYER_TYPE_NORMAL: u16 = 0;
+
+// Indexed images can not use more.
+const MAX_PALETTE_SIZE: usize = 256;
+
+const CEL_RAW: u16 = 0;
+const CEL_LINKED: u16 = 1;
+const CEL_COMPRESSED: u16 = 2;
+
+struct Bytes<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Bytes<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], AsepriteError> {
+        let end = self.position + count;
+        let slice = self
+            .bytes
+            .get(self.position..end)
+            .ok_or_else(|| AsepriteError::Invalid("unexpected end of file".to_string()))?;
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8, AsepriteError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn word(&mut self) -> Result<u16, AsepriteError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn short(&mut self) -> Result<i16, AsepriteError> {
+        Ok(self.word()? as i16)
+    }
+
+    fn dword(&mut self) -> Result<u32, AsepriteError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, AsepriteError> {
+        let length = self.word()? as usize;
+        Ok(String::from_utf8_lossy(self.take(length)?).into_owned())
+    }
+}
+
+struct Layer {
+    // Including the visibility of the groups it belongs to.
+    visible: bool,
+    opacity: u8,
+}
+
+enum CelContent {
+    // Pixels in the file color depth.
+    Image {
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    },
+    Linked(usize),
+}
+
+struct Cel {
+    layer: usize,
+    x: i32,
+    y: i32,
+    opacity: u8,
+    content: CelContent,
+}
+
+struct Frame {
+    duration: Duration,
+    cels: Vec<Cel>,
+}
+
+struct AsepriteFile {
+    width: u32,
+    height: u32,
+    // 32 (RGBA), 16 (grayscale and alpha) or 8 (indexed).
+    color_depth: u16,
+    layer_opacity: bool,
+    transparent_index: u8,
+    layers: Vec<Layer>,
+    palette: Vec<[u8; 4]>,
+    frames: Vec<Frame>,
+    tags: HashMap<String, AsepriteTag>,
+}
+
+impl AsepriteFile {
+    fn parse(bytes: &[u8]) -> Result<Self, AsepriteError> {
+        let mut header = Bytes::new(bytes);
+        header.dword()?;
+        if header.word()? != FILE_MAGIC {
+            return Err(AsepriteError::Invalid("not an Aseprite file".to_string()));
+        }
+
+        let frame_count = header.word()?;
+        let mut file = AsepriteFile {
+            width: header.word()? as u32,
+            height: header.word()? as u32,
+            color_depth: header.word()?,
+            layer_opacity: header.dword()? & FLAG_LAYER_OPACITY != 0,
+            transparent_index: 0,
+            layers: Vec::new(),
+            palette: Vec::new(),
+            frames: Vec::new(),
+            tags: HashMap::new(),
+        };
+
+        if ![8, 16, 32].contains(&file.color_depth) {
+            return Err(AsepriteError::Invalid(format!(
+                "unsupported color depth {}",
+                file.color_depth
+            )));
+        }
+
+        // Speed and two reserved dwords.
+        header.take(10)?;
+        file.transparent_index = header.byte()?;
+
+        let mut frames = Bytes::new(bytes);
+        frames.take(HEADER_SIZE)?;
+
+        // Visibility of the enclosing groups, by child level.
+        let mut groups: Vec<bool> = Vec::new();
+
+        for _ in 0..frame_count {
+            let frame_start = frames.position;
+            let frame_size = frames.dword()? as usize;
+            if frames.word()? != FRAME_MAGIC {
+                return Err(AsepriteError::Invalid("bad frame header".to_string()));
+            }
+
+            let old_chunk_count = frames.word()?;
+            let duration = Duration::from_millis(frames.word()? as u64);
+            frames.take(2)?;
+            let chunk_count = match frames.dword()? {
+                0 => old_chunk_count as u32,
+                count => count,
+            };
+
+            let mut frame = Frame {
+                duration,
+                cels: Vec::new(),
+            };
+
+            for _ in 0..chunk_count {
+                let chunk_size = frames.dword()? as usize;
+                let chunk_type = frames.word()?;
+                let mut chunk = Bytes::new(frames.take(chunk_size.saturating_sub(6))?);
+
+                match chunk_type {
+                    CHUNK_LAYER => file.parse_layer(&mut chunk, &mut groups)?,
+                    CHUNK_CEL => frame.cels.push(file.parse_cel(&mut chunk)?),
+                    CHUNK_TAGS => file.parse_tags(&mut chunk)?,
+                    CHUNK_PALETTE => file.parse_palette(&mut chunk)?,
+                    _ => {}
+                }
+            }
+
+            file.frames.push(frame);
+            frames.position = frame_start + frame_size;
+        }
+
+        if file.frames.is_empty() {
+            return Err(AsepriteError::Invalid("no frames".to_string()));
+        }
+
+        if let Some((name, _)) = file
+            .tags
+            .iter()
+            .find(|(_, tag)| tag.from > tag.to || tag.to >= file.frames.len())
+        {
+            return Err(AsepriteError::Invalid(format!(
+                "tag {} is out of the frame range",
+                name
+            )));
+        }
+
+        Ok(file)
+    }
+
+    fn parse_layer(
+        &mut self,
+        chunk: &mut Bytes,
+        groups: &mut Vec<bool>,
+    ) -> Result<(), AsepriteError> {
+        let flags = chunk.word()?;
+        let layer_type = chunk.word()?;
+        let child_level = chunk.word()? as usize;
+        // Default size and blend mode.
+        chunk.take(6)?;
+        let opacity = chunk.byte()?;
+
+        let parent_visible = child_level == 0 || groups.get(child_level - 1) == Some(&true);
+        let visible = flags & LAYER_VISIBLE != 0 && parent_visible;
+        groups.truncate(child_level);
+        groups.push(visible);
+
+        self.layers.push(Layer {
+            // Groups hold no pixels, reference layers are only shown while drawing.
+            visible: visible && layer_type == LAYER_TYPE_NORMAL && flags & LAYER_REFERENCE == 0,
+            opacity: if self.layer_opacity { opacity } else { 255 },
+        });
+
+        Ok(())
+    }
+
+    fn parse_cel(&self, chunk: &mut Bytes) -> Result<Cel, AsepriteError> {
+        let layer = chunk.word()? as usize;
+        let x = chunk.short()? as i32;
+        let y = chunk.short()? as i32;
+        let opacity = chunk.byte()?;
+        let cel_type = chunk.word()?;
+        // Z-index and reserved bytes.
+        chunk.take(7)?;
+
+        let content = match cel_type {
+            CEL_LINKED => CelContent::Linked(chunk.word()? as usize),
+            CEL_RAW | CEL_COMPRESSED => {
+                let width = chunk.word()? as u32;
+                let height = chunk.word()? as u32;
+                let data = chunk.take(chunk.bytes.len() - chunk.position)?;
+
+                let pixels = if cel_type == CEL_RAW {
+                    data.to_vec()
+                } else {
+                    let mut pixels = Vec::new();
+                    ZlibDecoder::new(data)
+                        .read_to_end(&mut pixels)
+                        .map_err(AsepriteError::Io)?;
+                    pixels
+                };
+
+                let expected = (width * height) as usize * self.bytes_per_pixel();
+                if pixels.len() < expected {
+                    return Err(AsepriteError::Invalid("truncated cel".to_string()));
+                }
+
+                CelContent::Image {
+                    width,
+                    height,
+                    pixels,
+                }
+            }
+            // Tilemaps.
+            _ => CelContent::Image {
+                width: 0,
+                height: 0,
+                pixels: Vec::new(),
+            },
+        };
+
+        Ok(Cel {
+            layer,
+            x,
+            y,
+            opacity,
+            content,
+        })
+    }
+
+    fn parse_tags(&mut self, chunk: &mut Bytes) -> Result<(), AsepriteError> {
+        let count = chunk.word()?;
+        chunk.take(8)?;
+
+        for _ in 0..count {
+            let from = chunk.word()? as usize;
+            let to = chunk.word()? as usize;
+            let direction = match chunk.byte()? {
+                1 => AsepriteDirection::Reverse,
+                2 => AsepriteDirection::PingPong,
+                3 => AsepriteDirection::PingPongReverse,
+                _ => AsepriteDirection::Forward,
+            };
+            let repeat = chunk.word()?;
+            // Reserved bytes and the deprecated tag color.
+            chunk.take(10)?;
+            let name = chunk.string()?;
+
+            self.tags.insert(
+                name,
+                AsepriteTag {
+                    from,
+                    to,
+                    direction,
+                    repeat,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    fn parse_palette(&mut self, chunk: &mut Bytes) -> Result<(), AsepriteError> {
+        let size = chunk.dword()? as usize;
+        let first = chunk.dword()? as usize;
+        let last = chunk.dword()? as usize;
+        chunk.take(8)?;
+
+        if first > last || last >= MAX_PALETTE_SIZE {
+            return Err(AsepriteError::Invalid(format!(
+                "palette entries {} to {} out of range",
+                first, last
+            )));
+        }
+
+        let size = size.clamp(last + 1, MAX_PALETTE_SIZE);
+        self.palette.resize(size.max(self.palette.len()), [0; 4]);
+
+        for index in first..=last {
+            let flags = chunk.word()?;
+            let color = [chunk.byte()?, chunk.byte()?, chunk.byte()?, chunk.byte()?];
+            if flags & 1 != 0 {
+                chunk.string()?;
+            }
+
+            if let Some(entry) = self.palette.get_mut(index) {
+                *entry = color;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn bytes_per_pixel(&self) -> usize {
+        self.color_depth as usize / 8
+    }
+
+    fn rgba(&self, pixel: &[u8]) -> [u8; 4] {
+        match self.color_depth {
+            32 => [pixel[0], pixel[1], pixel[2], pixel[3]],
+            16 => [pixel[0], pixel[0], pixel[0], pixel[1]],
+            _ if pixel[0] == self.transparent_index => [0; 4],
+            _ => self
+                .palette
+                .get(pixel[0] as usize)
+                .copied()
+                .unwrap_or([0; 4]),
+        }
+    }
+
+    // Follows a link to the cel holding the pixels. Links to other links, which Aseprite does
+    // not write, are not followed, so that cycles can not recurse forever.
+    fn cel_image<'a>(&'a self, cel: &'a Cel) -> Option<(u32, u32, &'a [u8])> {
+        let content = match &cel.content {
+            CelContent::Linked(linked) => {
+                &self
+                    .frames
+                    .get(*linked)?
+                    .cels
+                    .iter()
+                    .find(|other| other.layer == cel.layer)?
+                    .content
+            }
+            content => content,
+        };
+
+        match content {
+            CelContent::Image {
+                width,
+                height,
+                pixels,
+            } => Some((*width, *height, pixels)),
+            CelContent::Linked(_) => None,
+        }
+    }
+
+    // Frames flattened and laid out on a grid, as RGBA pixels, with its column and row counts.
+    fn atlas_pixels(&self) -> (Vec<u8>, u32, u32) {
+        let count = self.frames.len() as u32;
+        let columns = (count as f32).sqrt().ceil() as u32;
+        let rows = count.div_ceil(columns);
+        let atlas_width = self.width * columns;
+
+        let mut pixels = vec![0u8; (atlas_width * self.height * rows) as usize * 4];
+
+        for (index, frame) in self.frames.iter().enumerate() {
+            let origin_x = (index as u32 % columns) * self.width;
+            let origin_y = (index as u32 / columns) * self.height;
+
+            let mut cels: Vec<&Cel> = frame.cels.iter().collect();
+            cels.sort_by_key(|cel| cel.layer);
+
+            for cel in cels {
+                let Some(layer) = self.layers.get(cel.layer).filter(|layer| layer.visible) else {
+                    continue;
+                };
+                let Some((width, height, cel_pixels)) = self.cel_image(cel) else {
+                    continue;
+                };
+
+                let opacity = cel.opacity as f32 / 255.0 * layer.opacity as f32 / 255.0;
+
+                for cel_y in 0..height {
+                    for cel_x in 0..width {
+                        let x = cel.x + cel_x as i32;
+                        let y = cel.y + cel_y as i32;
+                        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+                            continue;
+                        }
+
+                        let source = (cel_y * width + cel_x) as usize * self.bytes_per_pixel();
+                        let color = self.rgba(&cel_pixels[source..]);
+
+                        let target = (((origin_y + y as u32) * atlas_width) + origin_x + x as u32)
+                            as usize
+                            * 4;
+                        blend(&mut pixels[target..target + 4], color, opacity);
+                    }
+                }
+            }
+        }
+
+        (pixels, columns, rows)
+    }
+}
+
+// Source over, on straight alpha.
+fn blend(target: &mut [u8], source: [u8; 4], opacity: f32) {
+    let source_alpha = source[3] as f32 / 255.0 * opacity;
+    if source_alpha <= 0.0 {
+        return;
+    }
+
+    let target_alpha = target[3] as f32 / 255.0;
+    let alpha = source_alpha + target_alpha * (1.0 - source_alpha);
+
+    for channel in 0..3 {
+        let value = (source[channel] as f32 * source_alpha
+            + target[channel] as f32 * target_alpha * (1.0 - source_alpha))
+            / alpha;
+        target[channel] = value.round() as u8;
+    }
+    target[3] = (alpha * 255.0).round() as u8;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two 2x2 layers over four frames, with raw, compressed and linked cels.
+    const FIXTURE: &[u8] = include_bytes!("../tests/fixtures/runner.aseprite");
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    fn pixel(pixels: &[u8], atlas_width: u32, x: u32, y: u32) -> [u8; 4] {
+        let start = ((y * atlas_width + x) * 4) as usize;
+        pixels[start..start + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn parses_frames_and_durations() {
+        let file = AsepriteFile::parse(FIXTURE).unwrap();
+
+        assert_eq!((file.width, file.height), (2, 2));
+        let durations: Vec<u64> = file
+            .frames
+            .iter()
+            .map(|frame| frame.duration.as_millis() as u64)
+            .collect();
+        assert_eq!(durations, vec![100, 150, 200, 250]);
+    }
+
+    #[test]
+    fn parses_tags() {
+        let file = AsepriteFile::parse(FIXTURE).unwrap();
+        assert_eq!(file.tags.len(), 3);
+
+        let tag = |name: &str| {
+            let tag = &file.tags[name];
+            (tag.from, tag.to, tag.direction, tag.repeat)
+        };
+        assert_eq!(tag("run"), (0, 2, AsepriteDirection::Forward, 0));
+        assert_eq!(tag("land"), (3, 3, AsepriteDirection::Forward, 1));
+        assert_eq!(tag("bob"), (1, 2, AsepriteDirection::PingPong, 0));
+    }
+
+    #[test]
+    fn lays_frames_out_on_a_grid() {
+        let file = AsepriteFile::parse(FIXTURE).unwrap();
+        let (pixels, columns, rows) = file.atlas_pixels();
+        assert_eq!((columns, rows), (2, 2));
+
+        let width = file.width * columns;
+        assert_eq!(pixel(&pixels, width, 0, 0), RED);
+        assert_eq!(pixel(&pixels, width, 2, 0), BLUE);
+        // The third frame is linked to the second one.
+        assert_eq!(pixel(&pixels, width, 1, 3), BLUE);
+        // The second layer over the first one.
+        assert_eq!(pixel(&pixels, width, 2, 2), GREEN);
+        assert_eq!(pixel(&pixels, width, 3, 2), BLUE);
+    }
+
+    #[test]
+    fn rejects_tags_past_the_last_frame() {
+        let mut bytes = FIXTURE.to_vec();
+        // The `to` frame of the first tag, in the first frame's tags chunk.
+        let tags = bytes
+            .windows(2)
+            .position(|window| window == CHUNK_TAGS.to_le_bytes())
+            .unwrap();
+        bytes[tags + 14..tags + 16].copy_from_slice(&4u16.to_le_bytes());
+
+        assert!(matches!(
+            AsepriteFile::parse(&bytes),
+            Err(AsepriteError::Invalid(_))
+        ));
+    }
+
+    fn palette_chunk(size: u32, first: u32, last: u32) -> Vec<u8> {
+        let mut chunk = Vec::new();
+        for value in [size, first, last, 0, 0] {
+            chunk.extend_from_slice(&value.to_le_bytes());
+        }
+        for _ in first..=last.min(first.saturating_add(1)) {
+            chunk.extend_from_slice(&[0, 0, 255, 0, 0, 255]);
+        }
+        chunk
+    }
+
+    #[test]
+    fn palettes_are_bounded() {
+        let mut file = AsepriteFile::parse(FIXTURE).unwrap();
+
+        // A huge size is clamped rather than allocated.
+        file.parse_palette(&mut Bytes::new(&palette_chunk(u32::MAX, 0, 1)))
+            .unwrap();
+        assert_eq!(file.palette.len(), MAX_PALETTE_SIZE);
+        assert_eq!(file.palette[1], [255, 0, 0, 255]);
+
+        for (first, last) in [(0, 256), (5, 4), (u32::MAX, u32::MAX)] {
+            assert!(
+                matches!(
+                    file.parse_palette(&mut Bytes::new(&palette_chunk(16, first, last))),
+                    Err(AsepriteError::Invalid(_))
+                ),
+                "entries {} to {}",
+                first,
+                last
+            );
+        }
+    }
+
+    #[test]
+    fn cyclic_links_are_not_followed() {
+        let mut file = AsepriteFile::parse(FIXTURE).unwrap();
+        // The first two frames linked to each other.
+        for (frame, linked) in [(0, 1), (1, 0)] {
+            file.frames[frame].cels = vec![Cel {
+                layer: 0,
+                x: 0,
+                y: 0,
+                opacity: 255,
+                content: CelContent::Linked(linked),
+            }];
+        }
+
+        let (pixels, columns, _) = file.atlas_pixels();
+        let width = file.width * columns;
+        assert_eq!(pixel(&pixels, width, 0, 0), [0; 4]);
+        assert_eq!(pixel(&pixels, width, 2, 0), [0; 4]);
+        // The third frame is linked to the second one, which no longer holds pixels.
+        assert_eq!(pixel(&pixels, width, 1, 3), [0; 4]);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(AsepriteFile::parse(b"not an aseprite file").is_err());
+        assert!(AsepriteFile::parse(&FIXTURE[..200]).is_err());
+    }
+}
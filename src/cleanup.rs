@@ -0,0 +1,197 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+use bevy::state::state::StateTransitionSteps;
+
+// Despawns entities, along with their children, when the game leaves the states they belong to,
+// instead of each state listing what to clean up on exit. Add one plugin per states type.
+pub struct CleanupPlugin<S: States> {
+    states: PhantomData<fn() -> S>,
+}
+
+impl<S: States> Default for CleanupPlugin<S> {
+    fn default() -> Self {
+        Self {
+            states: PhantomData,
+        }
+    }
+}
+
+impl<S: States> Plugin for CleanupPlugin<S> {
+    fn build(&self, app: &mut App) {
+        // `OnExit` schedules are per state, this runs on every transition instead.
+        app.add_systems(
+            StateTransition,
+            despawn_on_exit::<S>.in_set(StateTransitionSteps::ExitSchedules),
+        );
+    }
+}
+
+// Like Bevy's `StateScoped`, but can be despawned on exiting any of several states.
+#[derive(Component, Debug, Clone)]
+pub struct DespawnOnExit<S: States> {
+    states: Vec<S>,
+}
+
+impl<S: States> DespawnOnExit<S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            states: vec![state],
+        }
+    }
+
+    // Also despawns on exiting `state`.
+    pub fn or(mut self, state: S) -> Self {
+        self.states.push(state);
+        self
+    }
+}
+
+pub fn despawn_on_exit<S: States>(
+    mut commands: Commands,
+    mut transitions: EventReader<StateTransitionEvent<S>>,
+    query: Query<(Entity, &DespawnOnExit<S>)>,
+) {
+    // At most one transition per frame and states type.
+    let Some(transition) = transitions.read().last() else {
+        return;
+    };
+
+    let Some(exited) = transition
+        .exited
+        .as_ref()
+        .filter(|exited| transition.entered.as_ref() != Some(*exited))
+    else {
+        return;
+    };
+
+    for (entity, despawn) in query.iter() {
+        if despawn.states.contains(exited) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+    use bevy::state::state::FreelyMutableState;
+
+    use super::*;
+
+    #[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+    enum Screen {
+        #[default]
+        Title,
+        Play,
+        Scores,
+    }
+
+    #[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
+    #[source(Screen = Screen::Play)]
+    enum Play {
+        #[default]
+        Running,
+        Paused,
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            StatesPlugin,
+            CleanupPlugin::<Screen>::default(),
+            CleanupPlugin::<Play>::default(),
+        ))
+        .init_state::<Screen>()
+        .add_sub_state::<Play>();
+        app.update();
+        app
+    }
+
+    fn go<S: FreelyMutableState>(app: &mut App, state: S) {
+        app.world_mut().resource_mut::<NextState<S>>().set(state);
+        app.update();
+    }
+
+    fn exists(app: &App, entity: Entity) -> bool {
+        app.world().get_entity(entity).is_ok()
+    }
+
+    #[test]
+    fn despawns_on_exiting_the_state() {
+        let mut app = app();
+        let title = app
+            .world_mut()
+            .spawn(DespawnOnExit::new(Screen::Title))
+            .id();
+        let child = app.world_mut().spawn_empty().set_parent(title).id();
+        let play = app.world_mut().spawn(DespawnOnExit::new(Screen::Play)).id();
+        let kept = app.world_mut().spawn_empty().id();
+
+        go(&mut app, Screen::Play);
+
+        assert!(!exists(&app, title));
+        assert!(!exists(&app, child));
+        assert!(exists(&app, play));
+        assert!(exists(&app, kept));
+    }
+
+    #[test]
+    fn despawns_on_exiting_any_of_the_states() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(DespawnOnExit::new(Screen::Play).or(Screen::Scores))
+            .id();
+
+        go(&mut app, Screen::Scores);
+        assert!(exists(&app, entity));
+
+        go(&mut app, Screen::Title);
+        assert!(!exists(&app, entity));
+    }
+
+    #[test]
+    fn reentering_the_same_state_keeps_entities() {
+        let mut app = app();
+        let entity = app
+            .world_mut()
+            .spawn(DespawnOnExit::new(Screen::Title))
+            .id();
+
+        go(&mut app, Screen::Title);
+
+        assert!(exists(&app, entity));
+    }
+
+    #[test]
+    fn despawns_on_resuming_from_a_pause() {
+        let mut app = app();
+        go(&mut app, Screen::Play);
+        go(&mut app, Play::Paused);
+
+        let menu = app.world_mut().spawn(DespawnOnExit::new(Play::Paused)).id();
+        let run = app.world_mut().spawn(DespawnOnExit::new(Screen::Play)).id();
+
+        go(&mut app, Play::Running);
+
+        assert!(!exists(&app, menu));
+        assert!(exists(&app, run));
+    }
+
+    #[test]
+    fn despawns_substates_on_leaving_their_source() {
+        let mut app = app();
+        go(&mut app, Screen::Play);
+        go(&mut app, Play::Paused);
+
+        let menu = app.world_mut().spawn(DespawnOnExit::new(Play::Paused)).id();
+        let run = app.world_mut().spawn(DespawnOnExit::new(Screen::Play)).id();
+
+        // Quitting from the pause menu.
+        go(&mut app, Screen::Title);
+
+        assert!(!exists(&app, menu));
+        assert!(!exists(&app, run));
+    }
+}
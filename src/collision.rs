@@ -0,0 +1,547 @@
+use bevy::math::bounding::Aabb2d;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+// Overlap tests between hitboxes, reported as they start, go on and end, so that games know what
+// touched what and how deep, instead of polling every tick.
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Contacts>()
+            .add_event::<CollisionStarted>()
+            .add_event::<CollisionOngoing>()
+            .add_event::<CollisionEnded>()
+            .add_systems(FixedUpdate, detect_collisions.in_set(CollisionSet));
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CollisionSet;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitboxShape {
+    // Full size, in pixels.
+    Rectangle(Vec2),
+    Circle(f32),
+}
+
+// Collision shape, independent of how big the entity is drawn. Ignores the transform scale and
+// rotation.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Hitbox {
+    pub shape: HitboxShape,
+    // From the entity translation to the shape center.
+    pub offset: Vec2,
+}
+
+impl Hitbox {
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            shape: HitboxShape::Rectangle(size),
+            offset: Vec2::ZERO,
+        }
+    }
+
+    pub fn circle(radius: f32) -> Self {
+        Self {
+            shape: HitboxShape::Circle(radius),
+            offset: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn center(&self, transform: &Transform) -> Vec2 {
+        transform.translation.truncate() + self.offset
+    }
+
+    // Bounding box of the shape.
+    pub fn aabb(&self, transform: &Transform) -> Aabb2d {
+        let half_size = match self.shape {
+            HitboxShape::Rectangle(size) => size / 2.,
+            HitboxShape::Circle(radius) => Vec2::splat(radius),
+        };
+
+        Aabb2d::new(self.center(transform), half_size)
+    }
+}
+
+// Which hitboxes are tested against each other: both have to be interested in the other's layers.
+// Hitboxes without it are on every layer and collide with every layer.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CollisionLayers {
+    pub memberships: u32,
+    pub filters: u32,
+}
+
+impl CollisionLayers {
+    pub const ALL: u32 = u32::MAX;
+
+    pub fn new(memberships: u32, filters: u32) -> Self {
+        Self {
+            memberships,
+            filters,
+        }
+    }
+
+    pub fn interacts_with(&self, other: &CollisionLayers) -> bool {
+        self.filters & other.memberships != 0 && other.filters & self.memberships != 0
+    }
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        Self::new(Self::ALL, Self::ALL)
+    }
+}
+
+// Two overlapping hitboxes. `a` is always the lowest entity of the two.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    pub a: Entity,
+    pub b: Entity,
+    // Unit vector from `a` towards `b`, along which they overlap the least.
+    pub normal: Vec2,
+    // How far `b` has to move along `normal` to stop overlapping, in pixels.
+    pub depth: f32,
+}
+
+impl Contact {
+    // The entity `entity` collided with, when it is one of the two.
+    pub fn other(&self, entity: Entity) -> Option<Entity> {
+        if entity == self.a {
+            Some(self.b)
+        } else if entity == self.b {
+            Some(self.a)
+        } else {
+            None
+        }
+    }
+
+    // Normal pointing away from `entity`, when it is one of the two.
+    pub fn normal_from(&self, entity: Entity) -> Option<Vec2> {
+        if entity == self.a {
+            Some(self.normal)
+        } else if entity == self.b {
+            Some(-self.normal)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollisionStarted(pub Contact);
+
+// Every tick after the first one, while the overlap lasts.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollisionOngoing(pub Contact);
+
+// Holds the last contact. Also sent when one of the two lost its hitbox or was despawned.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CollisionEnded(pub Contact);
+
+// Pairs overlapping as of the last test.
+#[derive(Resource, Default, Debug)]
+pub struct Contacts {
+    pairs: HashMap<(Entity, Entity), Contact>,
+}
+
+impl Contacts {
+    pub fn get(&self, a: Entity, b: Entity) -> Option<&Contact> {
+        self.pairs.get(&(a.min(b), a.max(b)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Contact> {
+        self.pairs.values()
+    }
+
+    // Everything touching `entity`.
+    pub fn with(&self, entity: Entity) -> impl Iterator<Item = &Contact> {
+        self.pairs
+            .values()
+            .filter(move |contact| contact.other(entity).is_some())
+    }
+}
+
+pub fn detect_collisions(
+    hitboxes: Query<(Entity, &Transform, &Hitbox, Option<&CollisionLayers>)>,
+    mut contacts: ResMut<Contacts>,
+    mut started: EventWriter<CollisionStarted>,
+    mut ongoing: EventWriter<CollisionOngoing>,
+    mut ended: EventWriter<CollisionEnded>,
+) {
+    let mut previous = std::mem::take(&mut contacts.pairs);
+    let mut current = Vec::new();
+
+    for [first, second] in hitboxes.iter_combinations() {
+        let (first, second) = if first.0 < second.0 {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        let (a, a_transform, a_hitbox, a_layers) = first;
+        let (b, b_transform, b_hitbox, b_layers) = second;
+
+        let a_layers = a_layers.copied().unwrap_or_default();
+        if !a_layers.interacts_with(&b_layers.copied().unwrap_or_default()) {
+            continue;
+        }
+
+        let Some((normal, depth)) = overlap(
+            a_hitbox.shape,
+            a_hitbox.center(a_transform),
+            b_hitbox.shape,
+            b_hitbox.center(b_transform),
+        ) else {
+            continue;
+        };
+
+        current.push(Contact {
+            a,
+            b,
+            normal,
+            depth,
+        });
+    }
+
+    // Entity order, rather than the query's, keeps events the same from a run to the next.
+    current.sort_by_key(|contact| (contact.a, contact.b));
+
+    for contact in current {
+        if previous.remove(&(contact.a, contact.b)).is_some() {
+            ongoing.send(CollisionOngoing(contact));
+        } else {
+            started.send(CollisionStarted(contact));
+        }
+
+        contacts.pairs.insert((contact.a, contact.b), contact);
+    }
+
+    let mut stale: Vec<Contact> = previous.into_values().collect();
+    stale.sort_by_key(|contact| (contact.a, contact.b));
+    ended.send_batch(stale.into_iter().map(CollisionEnded));
+}
+
+// Normal from `a` towards `b`, and depth.
+fn overlap(a: HitboxShape, a_center: Vec2, b: HitboxShape, b_center: Vec2) -> Option<(Vec2, f32)> {
+    match (a, b) {
+        (HitboxShape::Rectangle(a_size), HitboxShape::Rectangle(b_size)) => {
+            let delta = b_center - a_center;
+            let overlap = (a_size + b_size) / 2. - delta.abs();
+
+            if overlap.x <= 0.0 || overlap.y <= 0.0 {
+                return None;
+            }
+
+            if overlap.x < overlap.y {
+                Some((Vec2::new(sign(delta.x), 0.0), overlap.x))
+            } else {
+                Some((Vec2::new(0.0, sign(delta.y)), overlap.y))
+            }
+        }
+        (HitboxShape::Circle(a_radius), HitboxShape::Circle(b_radius)) => {
+            let delta = b_center - a_center;
+            let distance = delta.length();
+            let depth = a_radius + b_radius - distance;
+
+            if depth <= 0.0 {
+                return None;
+            }
+
+            Some((delta.try_normalize().unwrap_or(Vec2::Y), depth))
+        }
+        (HitboxShape::Rectangle(size), HitboxShape::Circle(radius)) => {
+            rectangle_circle(size, a_center, radius, b_center)
+        }
+        (HitboxShape::Circle(radius), HitboxShape::Rectangle(size)) => {
+            rectangle_circle(size, b_center, radius, a_center)
+                .map(|(normal, depth)| (-normal, depth))
+        }
+    }
+}
+
+// Normal from the rectangle towards the circle.
+fn rectangle_circle(
+    size: Vec2,
+    rectangle_center: Vec2,
+    radius: f32,
+    circle_center: Vec2,
+) -> Option<(Vec2, f32)> {
+    let half_size = size / 2.;
+    let local = circle_center - rectangle_center;
+    let closest = local.clamp(-half_size, half_size);
+
+    if closest != local {
+        let delta = local - closest;
+        let distance = delta.length();
+
+        if distance >= radius {
+            return None;
+        }
+
+        return Some((delta / distance, radius - distance));
+    }
+
+    // Center inside the rectangle: out through the nearest side.
+    let to_side = half_size - local.abs();
+
+    if to_side.x < to_side.y {
+        Some((Vec2::new(sign(local.x), 0.0), to_side.x + radius))
+    } else {
+        Some((Vec2::new(0.0, sign(local.y)), to_side.y + radius))
+    }
+}
+
+// Towards positive for 0, so that stacked centers still get a normal.
+fn sign(value: f32) -> f32 {
+    if value < 0.0 {
+        -1.0
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::SQRT_2;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    const SQUARE: HitboxShape = HitboxShape::Rectangle(Vec2::splat(10.0));
+
+    fn assert_contact(contact: Option<(Vec2, f32)>, normal: Vec2, depth: f32) {
+        let (actual_normal, actual_depth) = contact.expect("no contact");
+        assert!(
+            actual_normal.abs_diff_eq(normal, 1e-5) && (actual_depth - depth).abs() < 1e-5,
+            "got {:?}, expected {:?}",
+            (actual_normal, actual_depth),
+            (normal, depth)
+        );
+    }
+
+    #[test]
+    fn rectangles_overlap_along_the_shallowest_axis() {
+        assert_contact(
+            overlap(SQUARE, Vec2::ZERO, SQUARE, Vec2::new(8.0, 2.0)),
+            Vec2::X,
+            2.0,
+        );
+        assert_contact(
+            overlap(SQUARE, Vec2::ZERO, SQUARE, Vec2::new(1.0, -7.0)),
+            Vec2::NEG_Y,
+            3.0,
+        );
+    }
+
+    #[test]
+    fn circles_overlap_along_their_centers() {
+        let circle = HitboxShape::Circle(5.0);
+
+        assert_contact(
+            overlap(circle, Vec2::ZERO, circle, Vec2::new(0.0, 8.0)),
+            Vec2::Y,
+            2.0,
+        );
+        // Stacked centers still get a normal.
+        assert_contact(
+            overlap(circle, Vec2::ZERO, circle, Vec2::ZERO),
+            Vec2::Y,
+            10.0,
+        );
+    }
+
+    #[test]
+    fn circle_near_a_rectangle_corner() {
+        let circle = HitboxShape::Circle(2.0);
+
+        assert_contact(
+            overlap(SQUARE, Vec2::ZERO, circle, Vec2::new(6.0, 6.0)),
+            Vec2::ONE / SQRT_2,
+            2.0 - SQRT_2,
+        );
+        assert_eq!(
+            overlap(SQUARE, Vec2::ZERO, circle, Vec2::new(7.0, 7.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn circle_center_inside_a_rectangle() {
+        let circle = HitboxShape::Circle(2.0);
+
+        // Out through the closest side, by the distance to it plus the radius.
+        assert_contact(
+            overlap(SQUARE, Vec2::ZERO, circle, Vec2::new(4.0, 1.0)),
+            Vec2::X,
+            3.0,
+        );
+        // The normal still goes from `a` to `b`.
+        assert_contact(
+            overlap(circle, Vec2::new(4.0, 1.0), SQUARE, Vec2::ZERO),
+            Vec2::NEG_X,
+            3.0,
+        );
+    }
+
+    #[test]
+    fn touching_edges_are_no_contact() {
+        let circle = HitboxShape::Circle(5.0);
+
+        assert_eq!(
+            overlap(SQUARE, Vec2::ZERO, SQUARE, Vec2::new(10.0, 3.0)),
+            None
+        );
+        assert_eq!(
+            overlap(SQUARE, Vec2::ZERO, SQUARE, Vec2::new(0.0, -10.0)),
+            None
+        );
+        assert_eq!(
+            overlap(circle, Vec2::ZERO, circle, Vec2::new(6.0, 8.0)),
+            None
+        );
+        assert_eq!(
+            overlap(SQUARE, Vec2::ZERO, circle, Vec2::new(10.0, 0.0)),
+            None
+        );
+    }
+
+    // Pairs (a, b) of each kind of event sent by one test.
+    #[derive(Debug, Default, PartialEq)]
+    struct Tick {
+        started: Vec<(Entity, Entity)>,
+        ongoing: Vec<(Entity, Entity)>,
+        ended: Vec<(Entity, Entity)>,
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Contacts>();
+        world.init_resource::<Events<CollisionStarted>>();
+        world.init_resource::<Events<CollisionOngoing>>();
+        world.init_resource::<Events<CollisionEnded>>();
+        world
+    }
+
+    fn pairs<E: Event>(
+        world: &mut World,
+        contact: impl Fn(&E) -> Contact,
+    ) -> Vec<(Entity, Entity)> {
+        let mut events = world.resource_mut::<Events<E>>();
+        let pairs = events
+            .iter_current_update_events()
+            .map(|event| {
+                let contact = contact(event);
+                (contact.a, contact.b)
+            })
+            .collect();
+        events.update();
+        pairs
+    }
+
+    fn tick(world: &mut World) -> Tick {
+        world.run_system_once(detect_collisions).unwrap();
+
+        Tick {
+            started: pairs(world, |event: &CollisionStarted| event.0),
+            ongoing: pairs(world, |event: &CollisionOngoing| event.0),
+            ended: pairs(world, |event: &CollisionEnded| event.0),
+        }
+    }
+
+    fn spawn(world: &mut World, x: f32) -> Entity {
+        world
+            .spawn((
+                Hitbox::new(Vec2::splat(10.0)),
+                Transform::from_xyz(x, 0.0, 0.0),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn contacts_start_go_on_and_end() {
+        let mut world = world();
+        let a = spawn(&mut world, 0.0);
+        let b = spawn(&mut world, 5.0);
+
+        assert_eq!(
+            tick(&mut world),
+            Tick {
+                started: vec![(a, b)],
+                ..default()
+            }
+        );
+        assert_eq!(world.resource::<Contacts>().get(b, a).unwrap().depth, 5.0);
+
+        for _ in 0..2 {
+            assert_eq!(
+                tick(&mut world),
+                Tick {
+                    ongoing: vec![(a, b)],
+                    ..default()
+                }
+            );
+        }
+
+        world.get_mut::<Transform>(b).unwrap().translation.x = 20.0;
+        assert_eq!(
+            tick(&mut world),
+            Tick {
+                ended: vec![(a, b)],
+                ..default()
+            }
+        );
+        assert!(world.resource::<Contacts>().get(a, b).is_none());
+        assert_eq!(tick(&mut world), Tick::default());
+    }
+
+    #[test]
+    fn despawning_ends_contacts() {
+        let mut world = world();
+        let a = spawn(&mut world, 0.0);
+        let b = spawn(&mut world, 5.0);
+        let c = spawn(&mut world, -5.0);
+
+        let mut expected = vec![(a.min(b), a.max(b)), (a.min(c), a.max(c))];
+        expected.sort();
+        assert_eq!(tick(&mut world).started, expected);
+
+        world.despawn(a);
+        assert_eq!(tick(&mut world).ended, expected);
+        assert_eq!(world.resource::<Contacts>().iter().count(), 0);
+    }
+
+    #[test]
+    fn layers_filter_contacts() {
+        let mut world = world();
+        let a = spawn(&mut world, 0.0);
+        let b = spawn(&mut world, 5.0);
+        world.entity_mut(a).insert(CollisionLayers::new(1, 2));
+        world.entity_mut(b).insert(CollisionLayers::new(1, 2));
+
+        assert_eq!(tick(&mut world), Tick::default());
+
+        world.entity_mut(b).insert(CollisionLayers::new(2, 1));
+        assert_eq!(tick(&mut world).started, vec![(a, b)]);
+    }
+
+    #[test]
+    fn events_follow_entity_order() {
+        let mut world = world();
+        let a = spawn(&mut world, 0.0);
+        let b = spawn(&mut world, 2.0);
+        let c = spawn(&mut world, 4.0);
+        // In another table, so that queries no longer visit `b` between `a` and `c`.
+        world.entity_mut(b).insert(CollisionLayers::default());
+
+        let pairs = vec![(a, b), (a, c), (b, c)];
+        assert_eq!(tick(&mut world).started, pairs);
+        assert_eq!(tick(&mut world).ongoing, pairs);
+    }
+}
@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+
+use crate::actions::{Action, Actions, ActionsPlugin};
+
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ActionsPlugin>() {
+            app.add_plugins(ActionsPlugin);
+        }
+
+        app.init_resource::<Combos>()
+            .add_event::<ComboCompleted>()
+            .add_systems(FixedUpdate, detect_combos);
+    }
+}
+
+#[derive(Event, Debug, Clone)]
+pub struct ComboCompleted {
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ComboStep {
+    pub action: Action,
+    // Seconds allowed since the previous step. Ignored for the first step.
+    pub window: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Combo {
+    pub name: String,
+    pub steps: Vec<ComboStep>,
+    next_step: usize,
+    last_step_at: f32,
+}
+
+impl Combo {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            steps: Vec::new(),
+            next_step: 0,
+            last_step_at: 0.0,
+        }
+    }
+
+    pub fn then(mut self, action: Action, window: f32) -> Self {
+        self.steps.push(ComboStep { action, window });
+        self
+    }
+
+    pub fn reset(&mut self) {
+        self.next_step = 0;
+        self.last_step_at = 0.0;
+    }
+
+    // Feeds a freshly pressed action, returns true when it completes the sequence.
+    pub fn advance(&mut self, action: Action, now: f32) -> bool {
+        if self.steps.is_empty() {
+            return false;
+        }
+
+        // A wrong press breaks the sequence, but the latest presses may still start it over: falls
+        // back to the longest prefix of the steps that they end with, as in KMP.
+        let matched = &self.steps[..self.next_step];
+        let Some(prefix) = (0..=self.next_step).rev().find(|&len| {
+            self.steps[len].action == action
+                && matched[self.next_step - len..]
+                    .iter()
+                    .zip(&self.steps[..len])
+                    .all(|(pressed, step)| pressed.action == step.action)
+        }) else {
+            self.reset();
+            return false;
+        };
+
+        self.next_step = prefix + 1;
+        self.last_step_at = now;
+
+        if self.next_step == self.steps.len() {
+            self.reset();
+            return true;
+        }
+
+        false
+    }
+
+    pub fn expire(&mut self, now: f32) {
+        if self.next_step > 0 && now - self.last_step_at > self.steps[self.next_step].window {
+            self.reset();
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Combos {
+    pub combos: Vec<Combo>,
+}
+
+impl Combos {
+    pub fn add(&mut self, combo: Combo) -> &mut Self {
+        self.combos.push(combo);
+        self
+    }
+}
+
+pub fn detect_combos(
+    mut combos: ResMut<Combos>,
+    actions: Res<Actions>,
+    time: Res<Time>,
+    mut events: EventWriter<ComboCompleted>,
+) {
+    let now = time.elapsed_secs();
+
+    for combo in combos.combos.iter_mut() {
+        combo.expire(now);
+
+        for action in Action::ALL {
+            if actions.just_pressed(action) && combo.advance(action, now) {
+                events.send(ComboCompleted {
+                    name: combo.name.clone(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn double_tap() -> Combo {
+        Combo::new("dash")
+            .then(Action::Left, 0.0)
+            .then(Action::Left, 0.25)
+    }
+
+    #[test]
+    fn completes_within_the_window() {
+        let mut combo = double_tap();
+
+        assert!(!combo.advance(Action::Left, 1.0));
+        combo.expire(1.2);
+        assert!(combo.advance(Action::Left, 1.2));
+    }
+
+    #[test]
+    fn starts_over_once_completed() {
+        let mut combo = double_tap();
+
+        combo.advance(Action::Left, 1.0);
+        assert!(combo.advance(Action::Left, 1.1));
+        assert!(!combo.advance(Action::Left, 1.2));
+        assert!(combo.advance(Action::Left, 1.3));
+    }
+
+    #[test]
+    fn expires_after_the_window() {
+        let mut combo = double_tap();
+
+        combo.advance(Action::Left, 1.0);
+        combo.expire(1.5);
+
+        // Counts as a first step again.
+        assert!(!combo.advance(Action::Left, 1.5));
+        assert!(combo.advance(Action::Left, 1.6));
+    }
+
+    #[test]
+    fn wrong_action_resets() {
+        let mut combo = double_tap();
+
+        combo.advance(Action::Left, 1.0);
+        assert!(!combo.advance(Action::Right, 1.1));
+        assert!(!combo.advance(Action::Left, 1.2));
+        assert!(combo.advance(Action::Left, 1.3));
+    }
+
+    #[test]
+    fn wrong_action_may_start_over() {
+        let mut combo = Combo::new("hadouken")
+            .then(Action::Down, 0.0)
+            .then(Action::Right, 0.5)
+            .then(Action::Jump, 0.5);
+
+        combo.advance(Action::Down, 1.0);
+        combo.advance(Action::Down, 1.1);
+        combo.advance(Action::Right, 1.2);
+        assert!(combo.advance(Action::Jump, 1.3));
+    }
+
+    #[test]
+    fn wrong_action_may_keep_a_prefix() {
+        let mut combo = Combo::new("slide")
+            .then(Action::Down, 0.0)
+            .then(Action::Down, 0.5)
+            .then(Action::Jump, 0.5);
+
+        combo.advance(Action::Down, 1.0);
+        combo.advance(Action::Down, 1.1);
+        // The last two presses still match the first two steps.
+        assert!(!combo.advance(Action::Down, 1.2));
+        assert!(combo.advance(Action::Jump, 1.3));
+    }
+
+    #[test]
+    fn empty_combo_never_completes() {
+        let mut combo = Combo::new("nothing");
+
+        assert!(!combo.advance(Action::Jump, 1.0));
+        combo.expire(2.0);
+    }
+}
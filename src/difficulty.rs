@@ -0,0 +1,236 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+// Turns elapsed time or score into a difficulty level, and the level into spawn rate and speed
+// multipliers. Level and curves are capped, so that a long run settles on a hard but steady pace.
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .add_systems(FixedUpdate, update_difficulty.in_set(DifficultySet));
+    }
+}
+
+// Games can add run conditions, such as only progressing while playing.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DifficultySet;
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum DifficultyProgression {
+    // Fixed time, so that replays progress the same way.
+    Time { seconds_per_level: f32 },
+    // Reported with `Difficulty::set_score`.
+    Score { points_per_level: f32 },
+}
+
+// Points of (level, value), linearly interpolated and held flat past both ends. An empty curve
+// is 1.0 at every level.
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(transparent)]
+pub struct DifficultyCurve(pub Vec<(f32, f32)>);
+
+impl DifficultyCurve {
+    pub fn sample(&self, level: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.0.first(), self.0.last()) else {
+            return 1.0;
+        };
+
+        if level <= first.0 {
+            return first.1;
+        }
+
+        self.0
+            .windows(2)
+            .find(|points| level < points[1].0)
+            .map_or(last.1, |points| {
+                let (start, end) = (points[0], points[1]);
+                let t = (level - start.0) / (end.0 - start.0);
+                start.1 + (end.1 - start.1) * t
+            })
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct DifficultySettings {
+    pub progression: DifficultyProgression,
+    pub max_level: f32,
+    #[serde(default)]
+    pub spawn_rate: DifficultyCurve,
+    #[serde(default)]
+    pub speed: DifficultyCurve,
+}
+
+impl Default for DifficultySettings {
+    fn default() -> Self {
+        Self {
+            progression: DifficultyProgression::Time {
+                seconds_per_level: 10.0,
+            },
+            max_level: 10.0,
+            spawn_rate: DifficultyCurve::default(),
+            speed: DifficultyCurve::default(),
+        }
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct Difficulty {
+    pub settings: DifficultySettings,
+    elapsed: f32,
+    score: u32,
+    level: f32,
+}
+
+impl Difficulty {
+    pub fn new(settings: DifficultySettings) -> Self {
+        Self {
+            settings,
+            ..default()
+        }
+    }
+
+    // Fractional, from 0.0 up to `max_level`.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    pub fn spawn_rate(&self) -> f32 {
+        self.settings.spawn_rate.sample(self.level)
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.settings.speed.sample(self.level)
+    }
+
+    pub fn is_unlocked(&self, level: f32) -> bool {
+        self.level >= level
+    }
+
+    pub fn set_score(&mut self, score: u32) {
+        self.score = score;
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+        self.score = 0;
+        self.level = 0.0;
+    }
+}
+
+pub fn reset_difficulty(mut difficulty: ResMut<Difficulty>) {
+    difficulty.reset();
+}
+
+pub fn update_difficulty(mut difficulty: ResMut<Difficulty>, time: Res<Time>) {
+    difficulty.elapsed += time.delta_secs();
+
+    let level = match difficulty.settings.progression {
+        DifficultyProgression::Time { seconds_per_level } => difficulty.elapsed / seconds_per_level,
+        DifficultyProgression::Score { points_per_level } => {
+            difficulty.score as f32 / points_per_level
+        }
+    };
+
+    difficulty.level = level.clamp(0.0, difficulty.settings.max_level);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn curve() -> DifficultyCurve {
+        DifficultyCurve(vec![(1.0, 1.0), (3.0, 2.0), (5.0, 4.0)])
+    }
+
+    fn world(settings: DifficultySettings) -> World {
+        let mut world = World::new();
+        world.insert_resource(Difficulty::new(settings));
+        world.init_resource::<Time>();
+        world
+    }
+
+    fn tick(world: &mut World, seconds: f32) {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        world.run_system_once(update_difficulty).unwrap();
+    }
+
+    #[test]
+    fn interpolates_between_points() {
+        let curve = curve();
+
+        assert_eq!(curve.sample(1.0), 1.0);
+        assert_eq!(curve.sample(2.0), 1.5);
+        assert_eq!(curve.sample(3.0), 2.0);
+        assert_eq!(curve.sample(4.5), 3.5);
+    }
+
+    #[test]
+    fn holds_flat_past_both_ends() {
+        let curve = curve();
+
+        assert_eq!(curve.sample(0.0), 1.0);
+        assert_eq!(curve.sample(-3.0), 1.0);
+        assert_eq!(curve.sample(5.0), 4.0);
+        assert_eq!(curve.sample(100.0), 4.0);
+    }
+
+    #[test]
+    fn empty_and_single_point_curves() {
+        assert_eq!(DifficultyCurve::default().sample(3.0), 1.0);
+
+        let flat = DifficultyCurve(vec![(2.0, 0.5)]);
+        assert_eq!(flat.sample(0.0), 0.5);
+        assert_eq!(flat.sample(10.0), 0.5);
+    }
+
+    #[test]
+    fn time_progression_is_capped() {
+        let mut world = world(DifficultySettings {
+            progression: DifficultyProgression::Time {
+                seconds_per_level: 10.0,
+            },
+            max_level: 2.0,
+            spawn_rate: DifficultyCurve::default(),
+            speed: curve(),
+        });
+
+        tick(&mut world, 15.0);
+        assert_eq!(world.resource::<Difficulty>().level(), 1.5);
+
+        tick(&mut world, 100.0);
+        let difficulty = world.resource::<Difficulty>();
+        assert_eq!(difficulty.level(), 2.0);
+        assert_eq!(difficulty.speed(), 1.5);
+    }
+
+    #[test]
+    fn score_progression_is_capped() {
+        let mut world = world(DifficultySettings {
+            progression: DifficultyProgression::Score {
+                points_per_level: 100.0,
+            },
+            max_level: 4.0,
+            ..default()
+        });
+
+        world.resource_mut::<Difficulty>().set_score(250);
+        tick(&mut world, 1.0);
+        assert_eq!(world.resource::<Difficulty>().level(), 2.5);
+        assert!(world.resource::<Difficulty>().is_unlocked(2.0));
+        assert!(!world.resource::<Difficulty>().is_unlocked(3.0));
+
+        world.resource_mut::<Difficulty>().set_score(10_000);
+        tick(&mut world, 1.0);
+        assert_eq!(world.resource::<Difficulty>().level(), 4.0);
+
+        world.resource_mut::<Difficulty>().reset();
+        assert_eq!(world.resource::<Difficulty>().level(), 0.0);
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bevy::animation::RepeatAnimation;
+use bevy::gltf::Gltf;
+use bevy::prelude::*;
+
+pub struct GltfAnimationPlugin;
+
+impl Plugin for GltfAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AnimationFinished>().add_systems(
+            Update,
+            (
+                build_animation_graphs,
+                attach_animation_players,
+                apply_animation_requests,
+                detect_finished_animations,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Event, Debug)]
+pub struct AnimationFinished {
+    // The entity holding the `GltfAnimations` component.
+    pub entity: Entity,
+    pub clip: String,
+}
+
+// Add next to a `SceneRoot` spawned from the same glTF file. Clips are addressed by the names
+// they were given in the file, and requests made before the scene is ready are kept until then.
+#[derive(Component)]
+pub struct GltfAnimations {
+    gltf: Handle<Gltf>,
+    graph: Option<Handle<AnimationGraph>>,
+    clips: HashMap<String, AnimationNodeIndex>,
+    player: Option<Entity>,
+    requested: Option<(String, Duration)>,
+    current: Option<String>,
+    finished: bool,
+    looping: bool,
+    speed: f32,
+}
+
+impl GltfAnimations {
+    pub fn new(gltf: Handle<Gltf>) -> Self {
+        Self {
+            gltf,
+            graph: None,
+            clips: HashMap::new(),
+            player: None,
+            requested: None,
+            current: None,
+            finished: false,
+            looping: true,
+            speed: 1.0,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.player.is_some()
+    }
+
+    // Sorted, so that cycling through clips is stable.
+    pub fn clip_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.clips.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn play(&mut self, clip: &str) {
+        self.crossfade(clip, Duration::ZERO);
+    }
+
+    pub fn crossfade(&mut self, clip: &str, duration: Duration) {
+        self.requested = Some((clip.to_string(), duration));
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+}
+
+pub fn build_animation_graphs(
+    mut query: Query<&mut GltfAnimations>,
+    gltfs: Res<Assets<Gltf>>,
+    mut graphs: ResMut<Assets<AnimationGraph>>,
+) {
+    for mut animations in query.iter_mut() {
+        if animations.graph.is_some() {
+            continue;
+        }
+
+        let Some(gltf) = gltfs.get(&animations.gltf) else {
+            continue;
+        };
+
+        let (names, handles): (Vec<String>, Vec<Handle<AnimationClip>>) = gltf
+            .named_animations
+            .iter()
+            .map(|(name, handle)| (name.to_string(), handle.clone()))
+            .unzip();
+
+        let (graph, nodes) = AnimationGraph::from_clips(handles);
+
+        animations.clips = names.into_iter().zip(nodes).collect();
+        animations.graph = Some(graphs.add(graph));
+    }
+}
+
+// The glTF loader puts the `AnimationPlayer` on the animated node, somewhere below the scene
+// root. It needs the graph and a transitions component to crossfade.
+pub fn attach_animation_players(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut GltfAnimations)>,
+    children: Query<&Children>,
+    players: Query<(), With<AnimationPlayer>>,
+) {
+    for (entity, mut animations) in query.iter_mut() {
+        if animations.player.is_some() {
+            continue;
+        }
+
+        let Some(graph) = animations.graph.clone() else {
+            continue;
+        };
+
+        let Some(player) = children
+            .iter_descendants(entity)
+            .find(|descendant| players.contains(*descendant))
+        else {
+            continue;
+        };
+
+        commands
+            .entity(player)
+            .insert((AnimationGraphHandle(graph), AnimationTransitions::new()));
+        animations.player = Some(player);
+    }
+}
+
+pub fn apply_animation_requests(
+    mut query: Query<&mut GltfAnimations, Changed<GltfAnimations>>,
+    mut players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    for mut animations in query.iter_mut() {
+        let Some(Ok((mut player, mut transitions))) =
+            animations.player.map(|player| players.get_mut(player))
+        else {
+            continue;
+        };
+
+        if let Some((clip, duration)) = animations.requested.take() {
+            match animations.clips.get(&clip) {
+                Some(node) => {
+                    transitions.play(&mut player, *node, duration);
+                    animations.current = Some(clip);
+                    animations.finished = false;
+                }
+                None => warn!("No animation clip named {}", clip),
+            }
+        }
+
+        let Some(node) = animations
+            .current
+            .as_ref()
+            .and_then(|clip| animations.clips.get(clip))
+        else {
+            continue;
+        };
+
+        if let Some(active) = player.animation_mut(*node) {
+            active
+                .set_repeat(if animations.looping {
+                    RepeatAnimation::Forever
+                } else {
+                    RepeatAnimation::Never
+                })
+                .set_speed(animations.speed);
+        }
+    }
+}
+
+pub fn detect_finished_animations(
+    mut query: Query<(Entity, &mut GltfAnimations)>,
+    players: Query<&AnimationPlayer>,
+    mut events: EventWriter<AnimationFinished>,
+) {
+    for (entity, mut animations) in query.iter_mut() {
+        if animations.finished {
+            continue;
+        }
+
+        let (Some(player), Some(clip)) = (animations.player, animations.current.clone()) else {
+            continue;
+        };
+
+        let finished = players
+            .get(player)
+            .ok()
+            .zip(animations.clips.get(&clip))
+            .and_then(|(player, node)| player.animation(*node))
+            .is_some_and(|active| active.is_finished());
+
+        if finished {
+            animations.finished = true;
+            events.send(AnimationFinished { entity, clip });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Assets<Gltf>>();
+        world.init_resource::<Assets<AnimationClip>>();
+        world.init_resource::<Assets<AnimationGraph>>();
+        world.init_resource::<Events<AnimationFinished>>();
+        world
+    }
+
+    fn update(world: &mut World) {
+        world.run_system_once(build_animation_graphs).unwrap();
+        world.run_system_once(attach_animation_players).unwrap();
+        world.run_system_once(apply_animation_requests).unwrap();
+        world.run_system_once(detect_finished_animations).unwrap();
+    }
+
+    fn gltf(world: &mut World, clips: &[&str]) -> Gltf {
+        let mut clip_assets = world.resource_mut::<Assets<AnimationClip>>();
+        let named_animations = clips
+            .iter()
+            .map(|clip| ((*clip).into(), clip_assets.add(AnimationClip::default())))
+            .collect();
+
+        Gltf {
+            scenes: Vec::new(),
+            named_scenes: default(),
+            meshes: Vec::new(),
+            named_meshes: default(),
+            materials: Vec::new(),
+            named_materials: default(),
+            nodes: Vec::new(),
+            named_nodes: default(),
+            skins: Vec::new(),
+            named_skins: default(),
+            default_scene: None,
+            animations: Vec::new(),
+            named_animations,
+            source: None,
+        }
+    }
+
+    // An entity holding the animations, as loaded scenes have their player further down.
+    fn spawn(world: &mut World, gltf: Handle<Gltf>) -> (Entity, Entity) {
+        let player = world.spawn(AnimationPlayer::default()).id();
+        let node = world.spawn_empty().add_child(player).id();
+        let root = world.spawn(GltfAnimations::new(gltf)).add_child(node).id();
+        (root, player)
+    }
+
+    fn animations(world: &World, entity: Entity) -> &GltfAnimations {
+        world.get::<GltfAnimations>(entity).unwrap()
+    }
+
+    #[test]
+    fn requests_wait_for_the_gltf() {
+        let mut world = world();
+        let handle = world.resource::<Assets<Gltf>>().reserve_handle();
+        let (root, player) = spawn(&mut world, handle.clone());
+
+        world.get_mut::<GltfAnimations>(root).unwrap().play("run");
+        update(&mut world);
+
+        assert!(!animations(&world, root).is_ready());
+        assert_eq!(animations(&world, root).current(), None);
+
+        let gltf = gltf(&mut world, &["run", "idle"]);
+        world.resource_mut::<Assets<Gltf>>().insert(&handle, gltf);
+        update(&mut world);
+
+        let animations = animations(&world, root);
+        assert!(animations.is_ready());
+        assert_eq!(animations.clip_names(), ["idle", "run"]);
+        assert_eq!(animations.current(), Some("run"));
+
+        let run = animations.clips["run"];
+        assert!(world.get::<AnimationGraphHandle>(player).is_some());
+        assert!(world
+            .get::<AnimationPlayer>(player)
+            .unwrap()
+            .is_playing_animation(run));
+    }
+
+    #[test]
+    fn unknown_clips_are_ignored() {
+        let mut world = world();
+        let gltf = gltf(&mut world, &["run"]);
+        let handle = world.resource_mut::<Assets<Gltf>>().add(gltf);
+        let (root, _) = spawn(&mut world, handle);
+
+        update(&mut world);
+        world.get_mut::<GltfAnimations>(root).unwrap().play("run");
+        update(&mut world);
+        world.get_mut::<GltfAnimations>(root).unwrap().play("fly");
+        update(&mut world);
+
+        assert_eq!(animations(&world, root).current(), Some("run"));
+    }
+
+    #[test]
+    fn looping_and_speed_reach_the_player() {
+        let mut world = world();
+        let gltf = gltf(&mut world, &["jump"]);
+        let handle = world.resource_mut::<Assets<Gltf>>().add(gltf);
+        let (root, player) = spawn(&mut world, handle);
+
+        update(&mut world);
+        let mut animations = world.get_mut::<GltfAnimations>(root).unwrap();
+        animations.set_looping(false);
+        animations.set_speed(2.0);
+        animations.play("jump");
+        update(&mut world);
+
+        let jump = self::animations(&world, root).clips["jump"];
+        let active = world
+            .get::<AnimationPlayer>(player)
+            .unwrap()
+            .animation(jump)
+            .unwrap();
+        assert_eq!(active.repeat_mode(), RepeatAnimation::Never);
+        assert_eq!(active.speed(), 2.0);
+    }
+}
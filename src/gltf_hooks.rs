@@ -0,0 +1,378 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::gltf::GltfExtras;
+use bevy::prelude::*;
+use bevy::scene::SceneInstanceReady;
+use serde_json::Value;
+
+// Maps named glTF nodes (and their Blender custom properties, exported as extras) to game
+// components once a scene has spawned.
+//
+// Conventions registered by default:
+// - `COL_*` nodes, or nodes with a `collider` extra, get a `BoxCollider` and are hidden.
+// - `SPAWN_*` nodes, or nodes with a `spawn_point` extra, get a `SpawnPoint`.
+// - `TRIGGER_*` nodes, or nodes with a `trigger` extra, get a `TriggerVolume` and are hidden.
+//
+// Box sizes come from the node scale, composed with its parents' up to the scene root, assuming
+// Blender's default 2x2x2 cube.
+pub struct GltfHooksPlugin;
+
+impl Plugin for GltfHooksPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GltfNodeHooks>()
+            .add_event::<GltfNodesMapped>()
+            .add_observer(map_gltf_nodes);
+    }
+}
+
+// Add to a `SceneRoot` entity to have its nodes mapped once spawned.
+#[derive(Component, Default)]
+pub struct MapGltfNodes;
+
+#[derive(Event)]
+pub struct GltfNodesMapped {
+    pub scene: Entity,
+}
+
+#[derive(Component, Debug)]
+pub struct BoxCollider {
+    pub half_size: Vec3,
+}
+
+#[derive(Component, Debug)]
+pub struct SpawnPoint {
+    pub name: String,
+}
+
+#[derive(Component, Debug)]
+pub struct TriggerVolume {
+    pub name: String,
+    pub half_size: Vec3,
+}
+
+pub struct GltfNode<'a> {
+    pub name: &'a str,
+    // `Value::Null` when the node has no extras.
+    pub extras: &'a Value,
+    pub transform: &'a Transform,
+    // Relative to the scene root, as global transforms are only propagated later in the frame.
+    pub scene_transform: Transform,
+}
+
+impl GltfNode<'_> {
+    // The node name without the convention prefix, or the string value of an extra.
+    fn label(&self, prefix: &str, extras_key: &str) -> String {
+        match self.extras.get(extras_key).and_then(Value::as_str) {
+            Some(label) => label.to_string(),
+            None => self
+                .name
+                .strip_prefix(prefix)
+                .unwrap_or(self.name)
+                .to_string(),
+        }
+    }
+}
+
+pub enum NodeMatcher {
+    Name(String),
+    NamePrefix(String),
+    ExtrasKey(String),
+}
+
+impl NodeMatcher {
+    pub fn matches(&self, node: &GltfNode) -> bool {
+        match self {
+            NodeMatcher::Name(name) => node.name == name,
+            NodeMatcher::NamePrefix(prefix) => node.name.starts_with(prefix.as_str()),
+            NodeMatcher::ExtrasKey(key) => node.extras.get(key).is_some(),
+        }
+    }
+}
+
+type NodeHook = Box<dyn Fn(&mut EntityCommands, &GltfNode) + Send + Sync>;
+
+#[derive(Resource)]
+pub struct GltfNodeHooks {
+    hooks: Vec<(Vec<NodeMatcher>, NodeHook)>,
+}
+
+impl GltfNodeHooks {
+    pub fn empty() -> Self {
+        Self { hooks: Vec::new() }
+    }
+
+    // Runs `hook` on every node matching any of `matchers`.
+    pub fn add(
+        &mut self,
+        matchers: Vec<NodeMatcher>,
+        hook: impl Fn(&mut EntityCommands, &GltfNode) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.hooks.push((matchers, Box::new(hook)));
+        self
+    }
+
+    pub fn on_name(
+        &mut self,
+        name: &str,
+        hook: impl Fn(&mut EntityCommands, &GltfNode) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.add(vec![NodeMatcher::Name(name.to_string())], hook)
+    }
+
+    pub fn on_convention(
+        &mut self,
+        prefix: &str,
+        extras_key: &str,
+        hook: impl Fn(&mut EntityCommands, &GltfNode) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.add(
+            vec![
+                NodeMatcher::NamePrefix(prefix.to_string()),
+                NodeMatcher::ExtrasKey(extras_key.to_string()),
+            ],
+            hook,
+        )
+    }
+}
+
+impl Default for GltfNodeHooks {
+    fn default() -> Self {
+        let mut hooks = Self::empty();
+
+        hooks
+            .on_convention("COL_", "collider", |entity, node| {
+                entity.insert((
+                    BoxCollider {
+                        half_size: node.scene_transform.scale,
+                    },
+                    Visibility::Hidden,
+                ));
+            })
+            .on_convention("SPAWN_", "spawn_point", |entity, node| {
+                entity.insert(SpawnPoint {
+                    name: node.label("SPAWN_", "spawn_point"),
+                });
+            })
+            .on_convention("TRIGGER_", "trigger", |entity, node| {
+                entity.insert((
+                    TriggerVolume {
+                        name: node.label("TRIGGER_", "trigger"),
+                        half_size: node.scene_transform.scale,
+                    },
+                    Visibility::Hidden,
+                ));
+            });
+
+        hooks
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn map_gltf_nodes(
+    trigger: Trigger<SceneInstanceReady>,
+    mut commands: Commands,
+    hooks: Res<GltfNodeHooks>,
+    scenes: Query<(), With<MapGltfNodes>>,
+    children: Query<&Children>,
+    parents: Query<&Parent>,
+    transforms: Query<&Transform>,
+    nodes: Query<(&Name, &Transform, Option<&GltfExtras>)>,
+    mut events: EventWriter<GltfNodesMapped>,
+) {
+    let scene = trigger.entity();
+
+    if !scenes.contains(scene) {
+        return;
+    }
+
+    for entity in children.iter_descendants(scene) {
+        let Ok((name, transform, extras)) = nodes.get(entity) else {
+            continue;
+        };
+
+        let extras = match extras.map(|extras| serde_json::from_str(&extras.value)) {
+            Some(Ok(value)) => value,
+            Some(Err(error)) => {
+                warn!("Invalid extras on glTF node {}: {}", name, error);
+                Value::Null
+            }
+            None => Value::Null,
+        };
+
+        let scene_transform = parents
+            .iter_ancestors(entity)
+            .take_while(|ancestor| *ancestor != scene)
+            .filter_map(|ancestor| transforms.get(ancestor).ok())
+            .fold(*transform, |transform, parent| {
+                parent.mul_transform(transform)
+            });
+
+        let node = GltfNode {
+            name: name.as_str(),
+            extras: &extras,
+            transform,
+            scene_transform,
+        };
+
+        for (matchers, hook) in hooks.hooks.iter() {
+            if matchers.iter().any(|matcher| matcher.matches(&node)) {
+                hook(&mut commands.entity(entity), &node);
+            }
+        }
+    }
+
+    events.send(GltfNodesMapped { scene });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::scene::ScenePlugin;
+    use serde_json::json;
+
+    use super::*;
+
+    fn node<'a>(name: &'a str, extras: &'a Value) -> GltfNode<'a> {
+        GltfNode {
+            name,
+            extras,
+            transform: &Transform::IDENTITY,
+            scene_transform: Transform::IDENTITY,
+        }
+    }
+
+    #[test]
+    fn matchers_check_names_and_extras() {
+        let extras = json!({ "collider": true });
+        let wall = node("COL_wall", &Value::Null);
+        let tagged = node("Cube.001", &extras);
+
+        assert!(NodeMatcher::Name("COL_wall".to_string()).matches(&wall));
+        assert!(!NodeMatcher::Name("COL_".to_string()).matches(&wall));
+        assert!(NodeMatcher::NamePrefix("COL_".to_string()).matches(&wall));
+        assert!(!NodeMatcher::NamePrefix("COL_".to_string()).matches(&tagged));
+        assert!(NodeMatcher::ExtrasKey("collider".to_string()).matches(&tagged));
+        assert!(!NodeMatcher::ExtrasKey("collider".to_string()).matches(&wall));
+    }
+
+    #[test]
+    fn labels_prefer_extras_over_names() {
+        let extras = json!({ "spawn_point": "boss" });
+
+        assert_eq!(
+            node("SPAWN_player", &Value::Null).label("SPAWN_", "spawn_point"),
+            "player"
+        );
+        assert_eq!(
+            node("Empty", &extras).label("SPAWN_", "spawn_point"),
+            "boss"
+        );
+        // Not a string, so the name is used as is.
+        assert_eq!(
+            node("Empty", &json!({ "spawn_point": true })).label("SPAWN_", "spawn_point"),
+            "Empty"
+        );
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            ScenePlugin,
+            TransformPlugin,
+            HierarchyPlugin,
+            GltfHooksPlugin,
+        ))
+        .register_type::<GltfExtras>();
+        app
+    }
+
+    // A scene as the glTF loader spawns it, with a scaled empty holding a collider.
+    fn scene() -> Scene {
+        let mut world = World::new();
+
+        world
+            .spawn((Name::new("Room"), Transform::from_scale(Vec3::splat(2.0))))
+            .with_children(|parent| {
+                parent.spawn((
+                    Name::new("COL_wall"),
+                    Transform::from_scale(Vec3::new(1.0, 3.0, 0.5)),
+                ));
+                parent.spawn((
+                    Name::new("Empty"),
+                    Transform::default(),
+                    GltfExtras {
+                        value: r#"{ "spawn_point": "player" }"#.to_string(),
+                    },
+                ));
+                parent.spawn((Name::new("TRIGGER_exit"), Transform::default()));
+            });
+
+        Scene::new(world)
+    }
+
+    #[test]
+    fn spawned_scenes_are_mapped() {
+        let mut app = app();
+        let scene = app.world_mut().resource_mut::<Assets<Scene>>().add(scene());
+        let root = app
+            .world_mut()
+            .spawn((
+                SceneRoot(scene),
+                MapGltfNodes,
+                // Not part of the scene's space.
+                Transform::from_scale(Vec3::splat(10.0)),
+            ))
+            .id();
+
+        app.update();
+
+        let world = app.world_mut();
+        let mapped = world
+            .resource_mut::<Events<GltfNodesMapped>>()
+            .drain()
+            .map(|event| event.scene)
+            .collect::<Vec<_>>();
+        assert_eq!(mapped, [root]);
+
+        let colliders = world
+            .query::<(&Name, &BoxCollider, &Visibility)>()
+            .iter(world)
+            .map(|(name, collider, visibility)| (name.to_string(), collider.half_size, *visibility))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            colliders,
+            [(
+                "COL_wall".to_string(),
+                Vec3::new(2.0, 6.0, 1.0),
+                Visibility::Hidden
+            )]
+        );
+
+        let spawn_points = world
+            .query::<&SpawnPoint>()
+            .iter(world)
+            .map(|spawn_point| spawn_point.name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(spawn_points, ["player"]);
+
+        let triggers = world
+            .query::<&TriggerVolume>()
+            .iter(world)
+            .map(|trigger| (trigger.name.clone(), trigger.half_size))
+            .collect::<Vec<_>>();
+        assert_eq!(triggers, [("exit".to_string(), Vec3::splat(2.0))]);
+    }
+
+    #[test]
+    fn scenes_without_the_marker_are_left_alone() {
+        let mut app = app();
+        let scene = app.world_mut().resource_mut::<Assets<Scene>>().add(scene());
+        app.world_mut().spawn(SceneRoot(scene));
+
+        app.update();
+
+        let world = app.world_mut();
+        assert!(world.resource::<Events<GltfNodesMapped>>().is_empty());
+        assert_eq!(world.query::<&BoxCollider>().iter(world).count(), 0);
+    }
+}
@@ -0,0 +1,350 @@
+use bevy::prelude::*;
+
+// Hit points for anything that can take damage. Games send `DamageEvent`s and listen to `Damaged`
+// for feedback and to `Died` for outcomes. After a hit, an entity shrugs off any damage for a
+// while, so that overlapping something for several ticks only counts once.
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<Damaged>()
+            .add_event::<Died>()
+            .add_systems(
+                FixedUpdate,
+                (tick_invulnerability, apply_damage, apply_knockback)
+                    .chain()
+                    .in_set(HealthSet),
+            );
+    }
+}
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HealthSet;
+
+#[derive(Event, Clone, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: u32,
+    // What dealt it, if anything.
+    pub source: Option<Entity>,
+    // Impulse for the target, in pixels per second.
+    pub knockback: Vec2,
+}
+
+impl DamageEvent {
+    pub fn new(target: Entity, amount: u32) -> Self {
+        Self {
+            target,
+            amount,
+            source: None,
+            knockback: Vec2::ZERO,
+        }
+    }
+
+    pub fn from(mut self, source: Entity) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn with_knockback(mut self, knockback: Vec2) -> Self {
+        self.knockback = knockback;
+        self
+    }
+}
+
+// Damage that went through, for hit feedback.
+#[derive(Event, Clone, Debug)]
+pub struct Damaged {
+    pub entity: Entity,
+    pub amount: u32,
+    pub source: Option<Entity>,
+    pub knockback: Vec2,
+    pub remaining: u32,
+}
+
+// Sent once, when health drops to zero.
+#[derive(Event, Clone, Debug)]
+pub struct Died {
+    pub entity: Entity,
+    pub source: Option<Entity>,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Health {
+    current: u32,
+    max: u32,
+    // Seconds of invulnerability after a hit.
+    pub invulnerability: f32,
+    invulnerable_for: f32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Self {
+            current: max,
+            max,
+            invulnerability: 0.0,
+            invulnerable_for: 0.0,
+        }
+    }
+
+    pub fn with_invulnerability(mut self, seconds: f32) -> Self {
+        self.invulnerability = seconds;
+        self
+    }
+
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current == 0
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable_for > 0.0
+    }
+
+    // Seconds left, for blinking sprites and the like.
+    pub fn invulnerable_for(&self) -> f32 {
+        self.invulnerable_for
+    }
+
+    // Up to the max, and not for the dead.
+    pub fn heal(&mut self, amount: u32) {
+        if !self.is_dead() {
+            self.current = self.current.saturating_add(amount).min(self.max);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.current = self.max;
+        self.invulnerable_for = 0.0;
+    }
+}
+
+// Receives the knockback of `Damaged`, moving the entity until friction stops it. Entities
+// without it leave knockback to the game, through `Damaged`.
+#[derive(Component, Debug, Clone)]
+pub struct Knockback {
+    // In pixels per second.
+    pub velocity: Vec2,
+    // Fraction of the velocity lost per second.
+    pub friction: f32,
+}
+
+impl Knockback {
+    pub fn new(friction: f32) -> Self {
+        Self {
+            velocity: Vec2::ZERO,
+            friction,
+        }
+    }
+}
+
+pub fn tick_invulnerability(mut healths: Query<&mut Health>, time: Res<Time>) {
+    for mut health in healths.iter_mut() {
+        if health.is_invulnerable() {
+            health.invulnerable_for = (health.invulnerable_for - time.delta_secs()).max(0.0);
+        }
+    }
+}
+
+pub fn apply_damage(
+    mut damage_events: EventReader<DamageEvent>,
+    mut healths: Query<(&mut Health, Option<&mut Knockback>)>,
+    mut damaged_events: EventWriter<Damaged>,
+    mut died_events: EventWriter<Died>,
+) {
+    for event in damage_events.read() {
+        let Ok((mut health, knockback)) = healths.get_mut(event.target) else {
+            continue;
+        };
+
+        if event.amount == 0 || health.is_dead() || health.is_invulnerable() {
+            continue;
+        }
+
+        health.current = health.current.saturating_sub(event.amount);
+        health.invulnerable_for = health.invulnerability;
+
+        if let Some(mut knockback) = knockback {
+            knockback.velocity += event.knockback;
+        }
+
+        damaged_events.send(Damaged {
+            entity: event.target,
+            amount: event.amount,
+            source: event.source,
+            knockback: event.knockback,
+            remaining: health.current,
+        });
+
+        if health.is_dead() {
+            died_events.send(Died {
+                entity: event.target,
+                source: event.source,
+            });
+        }
+    }
+}
+
+pub fn apply_knockback(mut query: Query<(&mut Transform, &mut Knockback)>, time: Res<Time>) {
+    let delta = time.delta_secs();
+
+    for (mut transform, mut knockback) in query.iter_mut() {
+        if knockback.velocity == Vec2::ZERO {
+            continue;
+        }
+
+        transform.translation += (knockback.velocity * delta).extend(0.0);
+
+        let remaining = (1.0 - knockback.friction * delta).max(0.0);
+        knockback.velocity *= remaining;
+
+        if knockback.velocity.length_squared() < 1.0 {
+            knockback.velocity = Vec2::ZERO;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    const TICK: Duration = Duration::from_millis(100);
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<DamageEvent>>();
+        world.init_resource::<Events<Damaged>>();
+        world.init_resource::<Events<Died>>();
+        world
+    }
+
+    fn drain<E: Event + Clone>(world: &mut World) -> Vec<E> {
+        let mut events = world.resource_mut::<Events<E>>();
+        let drained = events.iter_current_update_events().cloned().collect();
+        events.update();
+        drained
+    }
+
+    // Runs the systems as `HealthPlugin` does, returning the damage and deaths that went through.
+    fn tick(world: &mut World, damage: &[DamageEvent]) -> (Vec<Damaged>, Vec<Died>) {
+        world.resource_mut::<Time>().advance_by(TICK);
+        world.send_event_batch(damage.iter().cloned());
+
+        world.run_system_once(tick_invulnerability).unwrap();
+        world.run_system_once(apply_damage).unwrap();
+        world.run_system_once(apply_knockback).unwrap();
+        // Fresh readers would see them again on the next tick otherwise.
+        world.resource_mut::<Events<DamageEvent>>().clear();
+
+        (drain(world), drain(world))
+    }
+
+    fn health(world: &World, entity: Entity) -> &Health {
+        world.get::<Health>(entity).unwrap()
+    }
+
+    #[test]
+    fn damage_lowers_health() {
+        let mut world = world();
+        let target = world.spawn(Health::new(3)).id();
+        let source = world.spawn_empty().id();
+
+        let (damaged, died) = tick(&mut world, &[DamageEvent::new(target, 2).from(source)]);
+
+        assert_eq!(damaged.len(), 1);
+        assert_eq!(damaged[0].entity, target);
+        assert_eq!(damaged[0].source, Some(source));
+        assert_eq!(damaged[0].remaining, 1);
+        assert!(died.is_empty());
+        assert_eq!(health(&world, target).current(), 1);
+    }
+
+    #[test]
+    fn hits_while_invulnerable_are_ignored() {
+        let mut world = world();
+        let target = world.spawn(Health::new(3).with_invulnerability(0.25)).id();
+
+        // Both hits land on the same tick, only the first counts.
+        let hit = DamageEvent::new(target, 1);
+        let (damaged, _) = tick(&mut world, &[hit.clone(), hit.clone()]);
+        assert_eq!(damaged.len(), 1);
+        assert!(health(&world, target).is_invulnerable());
+
+        let (damaged, _) = tick(&mut world, &[hit]);
+        assert!(damaged.is_empty());
+        assert_eq!(health(&world, target).current(), 2);
+    }
+
+    #[test]
+    fn invulnerability_expires() {
+        let mut world = world();
+        let target = world.spawn(Health::new(3).with_invulnerability(0.25)).id();
+        let hit = DamageEvent::new(target, 1);
+
+        tick(&mut world, std::slice::from_ref(&hit));
+        tick(&mut world, &[]);
+        tick(&mut world, &[]);
+        assert!(health(&world, target).is_invulnerable());
+
+        // 0.3 seconds after the hit.
+        let (damaged, _) = tick(&mut world, &[hit]);
+        assert_eq!(damaged.len(), 1);
+        assert_eq!(health(&world, target).current(), 1);
+    }
+
+    #[test]
+    fn death_is_sent_once() {
+        let mut world = world();
+        let target = world.spawn(Health::new(2)).id();
+        let hit = DamageEvent::new(target, 3);
+
+        let (damaged, died) = tick(&mut world, &[hit.clone(), hit.clone()]);
+        assert_eq!(damaged.len(), 1);
+        assert_eq!(damaged[0].remaining, 0);
+        assert_eq!(died.len(), 1);
+        assert_eq!(died[0].entity, target);
+        assert!(health(&world, target).is_dead());
+
+        let (damaged, died) = tick(&mut world, &[hit]);
+        assert!(damaged.is_empty());
+        assert!(died.is_empty());
+    }
+
+    #[test]
+    fn knockback_pushes_and_slows_down() {
+        let mut world = world();
+        let target = world
+            .spawn((Health::new(3), Knockback::new(5.0), Transform::default()))
+            .id();
+
+        let (damaged, _) = tick(
+            &mut world,
+            &[DamageEvent::new(target, 1).with_knockback(Vec2::new(100.0, 0.0))],
+        );
+        assert_eq!(damaged[0].knockback, Vec2::new(100.0, 0.0));
+
+        // Moved by 100 * 0.1, then lost half its speed to friction.
+        assert_eq!(world.get::<Transform>(target).unwrap().translation.x, 10.0);
+        assert_eq!(world.get::<Knockback>(target).unwrap().velocity.x, 50.0);
+
+        for _ in 0..20 {
+            tick(&mut world, &[]);
+        }
+        assert_eq!(world.get::<Knockback>(target).unwrap().velocity, Vec2::ZERO);
+        assert!(world.get::<Transform>(target).unwrap().translation.x < 20.0);
+    }
+}
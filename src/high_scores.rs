@@ -0,0 +1,255 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Best scores of all time, kept in a RON file next to the game.
+pub struct HighScoresPlugin {
+    pub path: String,
+    // How many scores are kept.
+    pub capacity: usize,
+}
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load(&self.path, self.capacity));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    // To play the run again.
+    pub seed: u64,
+    // As YYYY-MM-DD, in UTC.
+    pub date: String,
+}
+
+impl HighScore {
+    // Dated today.
+    pub fn new(name: impl Into<String>, score: u32, seed: u64) -> Self {
+        Self {
+            name: name.into(),
+            score,
+            seed,
+            date: today(),
+        }
+    }
+}
+
+// Highest first.
+#[derive(Resource, Debug)]
+pub struct HighScores {
+    path: PathBuf,
+    capacity: usize,
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    // Starts empty when the file is missing or broken.
+    pub fn load(path: impl Into<PathBuf>, capacity: usize) -> Self {
+        let path = path.into();
+
+        let mut entries: Vec<HighScore> = match fs::read_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|error| {
+                warn!("Could not read high scores {}: {}", path.display(), error);
+                Vec::new()
+            }),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => {
+                warn!("Could not read high scores {}: {}", path.display(), error);
+                Vec::new()
+            }
+        };
+
+        // Hand edits, or a smaller capacity than before.
+        entries.sort_by_key(|entry| Reverse(entry.score));
+        entries.truncate(capacity);
+
+        Self {
+            path,
+            capacity,
+            entries,
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let contents = ron::ser::to_string_pretty(&self.entries, Default::default())
+            .map_err(io::Error::other)?;
+
+        fs::write(&self.path, contents)
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    // Whether `score` would make it into the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < self.capacity
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    // Returns the rank it got, from 0, or `None` when it did not make it. Ties go after older
+    // scores.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(self.capacity);
+
+        Some(rank)
+    }
+}
+
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400) as i64;
+
+    date_from_days(days)
+}
+
+// Civil from days, by Howard Hinnant. Days count from 1970-01-01.
+fn date_from_days(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u32) -> HighScore {
+        HighScore {
+            name: name.to_string(),
+            score,
+            seed: 0,
+            date: "2024-02-29".to_string(),
+        }
+    }
+
+    fn table(capacity: usize, scores: &[u32]) -> HighScores {
+        HighScores {
+            path: PathBuf::new(),
+            capacity,
+            entries: scores
+                .iter()
+                .enumerate()
+                .map(|(index, score)| entry(&index.to_string(), *score))
+                .collect(),
+        }
+    }
+
+    fn scores(high_scores: &HighScores) -> Vec<u32> {
+        high_scores
+            .entries()
+            .iter()
+            .map(|entry| entry.score)
+            .collect()
+    }
+
+    #[test]
+    fn dates_from_days() {
+        assert_eq!(date_from_days(0), "1970-01-01");
+        assert_eq!(date_from_days(-1), "1969-12-31");
+        assert_eq!(date_from_days(59), "1970-03-01");
+        assert_eq!(date_from_days(10_957), "2000-01-01");
+    }
+
+    #[test]
+    fn dates_in_leap_years() {
+        assert_eq!(date_from_days(11_016), "2000-02-29");
+        assert_eq!(date_from_days(11_017), "2000-03-01");
+        assert_eq!(date_from_days(19_782), "2024-02-29");
+        // Not a leap year, as a century not divisible by 400.
+        assert_eq!(date_from_days(47_540), "2100-02-28");
+        assert_eq!(date_from_days(47_541), "2100-03-01");
+    }
+
+    #[test]
+    fn full_table_only_takes_higher_scores() {
+        let high_scores = table(3, &[300, 200, 100]);
+
+        assert!(high_scores.qualifies(101));
+        assert!(!high_scores.qualifies(100));
+        assert!(!high_scores.qualifies(50));
+    }
+
+    #[test]
+    fn zero_never_qualifies() {
+        assert!(!table(3, &[]).qualifies(0));
+    }
+
+    #[test]
+    fn inserting_into_a_full_table_drops_the_lowest() {
+        let mut high_scores = table(3, &[300, 200, 100]);
+
+        assert_eq!(high_scores.insert(entry("new", 250)), Some(1));
+        assert_eq!(scores(&high_scores), vec![300, 250, 200]);
+
+        assert_eq!(high_scores.insert(entry("top", 400)), Some(0));
+        assert_eq!(scores(&high_scores), vec![400, 300, 250]);
+
+        assert_eq!(high_scores.insert(entry("low", 250)), None);
+        assert_eq!(scores(&high_scores), vec![400, 300, 250]);
+    }
+
+    #[test]
+    fn ties_go_after_older_scores() {
+        let mut high_scores = table(4, &[300, 200, 100]);
+
+        assert_eq!(high_scores.insert(entry("new", 200)), Some(2));
+        let names: Vec<&str> = high_scores
+            .entries()
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["0", "1", "new", "2"]);
+    }
+
+    #[test]
+    fn save_and_load_sorts_and_truncates() {
+        let path = std::env::temp_dir().join(format!("high-scores-{}.ron", std::process::id()));
+
+        let mut high_scores = table(4, &[100, 400, 300, 200]);
+        high_scores.path = path.clone();
+        high_scores.save().unwrap();
+
+        let loaded = HighScores::load(&path, 2);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(scores(&loaded), vec![400, 300]);
+    }
+
+    #[test]
+    fn missing_file_loads_empty() {
+        let path = std::env::temp_dir().join("high-scores-missing.ron");
+
+        assert!(HighScores::load(path, 3).entries().is_empty());
+    }
+}
@@ -1,5 +1,11 @@
 use bevy::prelude::*;
 
+use crate::actions::{Action, Actions, ActionsPlugin};
+
+// In screen units per second, and per second².
+const JUMP_VELOCITY: f32 = 70.0;
+const GRAVITY: f32 = 160.0;
+
 pub struct JumpPlugin {
     pub screen_unit: f32,
 }
@@ -9,11 +15,47 @@ pub struct JumpConfig {
     screen_unit: f32,
 }
 
+// Reach of a full jump, with the key held, for games to check what can be jumped over.
+impl JumpConfig {
+    // Pixels per screen unit.
+    pub fn new(screen_unit: f32) -> Self {
+        Self { screen_unit }
+    }
+
+    // Seconds from take-off to landing.
+    pub fn airtime(&self) -> f32 {
+        2.0 * JUMP_VELOCITY / GRAVITY
+    }
+
+    // In pixels.
+    pub fn max_height(&self) -> f32 {
+        JUMP_VELOCITY.powi(2) / (2.0 * GRAVITY) * self.screen_unit
+    }
+
+    // Seconds after take-off at which the player rises above `height` pixels, then falls back
+    // below it. `None` when out of reach.
+    pub fn time_above(&self, height: f32) -> Option<(f32, f32)> {
+        let discriminant = JUMP_VELOCITY.powi(2) - 2.0 * GRAVITY * height / self.screen_unit;
+
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        Some((
+            (JUMP_VELOCITY - root) / GRAVITY,
+            (JUMP_VELOCITY + root) / GRAVITY,
+        ))
+    }
+}
+
 impl Plugin for JumpPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(JumpConfig {
-            screen_unit: self.screen_unit,
-        });
+        if !app.is_plugin_added::<ActionsPlugin>() {
+            app.add_plugins(ActionsPlugin);
+        }
+
+        app.insert_resource(JumpConfig::new(self.screen_unit));
     }
 }
 
@@ -22,12 +64,21 @@ pub enum JumpingStates {
     Airborne,
 }
 
+// What the jump looks like, for animations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JumpPhase {
+    Grounded,
+    Rising,
+    Falling,
+}
+
 #[derive(Component)]
 pub struct JumpingState {
     state: JumpingStates,
     jump_started_at: f32,
     current_velocity: f32,
     key_was_released: bool,
+    rising: bool,
 }
 
 impl JumpingState {
@@ -37,6 +88,15 @@ impl JumpingState {
             jump_started_at: 0.0,
             current_velocity: 0.0,
             key_was_released: false,
+            rising: false,
+        }
+    }
+
+    pub fn phase(&self) -> JumpPhase {
+        match self.state {
+            JumpingStates::Idle => JumpPhase::Grounded,
+            JumpingStates::Airborne if self.rising => JumpPhase::Rising,
+            JumpingStates::Airborne => JumpPhase::Falling,
         }
     }
 
@@ -45,12 +105,13 @@ impl JumpingState {
         self.jump_started_at = 0.0;
         self.key_was_released = false;
         self.current_velocity = 0.0;
+        self.rising = false;
     }
 }
 
 pub fn handle_jumping_state(
     mut query: Query<&mut JumpingState>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<Actions>,
     time: Res<Time>,
 ) {
     if query.is_empty() {
@@ -59,23 +120,17 @@ pub fn handle_jumping_state(
 
     let mut jumping_state = query.single_mut();
 
-    if keyboard.just_pressed(KeyCode::Space) {
-        match jumping_state.state {
-            JumpingStates::Idle => {
-                jumping_state.state = JumpingStates::Airborne;
-                jumping_state.jump_started_at = time.elapsed_secs();
-            }
-            _ => {}
+    if actions.just_pressed(Action::Jump) {
+        if let JumpingStates::Idle = jumping_state.state {
+            jumping_state.state = JumpingStates::Airborne;
+            jumping_state.jump_started_at = time.elapsed_secs();
+            jumping_state.rising = true;
         }
     }
 
-    if keyboard.just_released(KeyCode::Space) {
-        match jumping_state.state {
-            JumpingStates::Airborne => {
-                jumping_state.key_was_released = true;
-            }
-
-            _ => {}
+    if actions.just_released(Action::Jump) {
+        if let JumpingStates::Airborne = jumping_state.state {
+            jumping_state.key_was_released = true;
         }
     }
 }
@@ -110,14 +165,15 @@ pub fn update_player_velocity(mut query: Query<&mut JumpingState>, time: Res<Tim
                 y = jumping_state.current_velocity - (0.55 + x);
             } else {
                 let h: f32 = 0.0;
-                let v: f32 = 70.0;
-                let g: f32 = 160.0;
+                let v: f32 = JUMP_VELOCITY;
+                let g: f32 = GRAVITY;
                 y = h + (v * x) - 0.5 * g * x.powi(2);
             }
 
             if y < 0.0 {
                 jumping_state.reset();
             } else {
+                jumping_state.rising = y > jumping_state.current_velocity;
                 jumping_state.current_velocity = y;
             }
         }
@@ -145,3 +201,59 @@ pub fn update_player_transform(
         transform.translation.y = jumping_state.current_velocity * jump_config.screen_unit;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN_UNIT: f32 = 4.0;
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn time_above_the_ground_is_the_airtime() {
+        let jump = JumpConfig::new(SCREEN_UNIT);
+
+        let (rises, falls) = jump.time_above(0.0).unwrap();
+        assert_near(rises, 0.0);
+        assert_near(falls, jump.airtime());
+    }
+
+    #[test]
+    fn time_above_is_symmetric_around_the_apex() {
+        let jump = JumpConfig::new(SCREEN_UNIT);
+        let apex = jump.airtime() / 2.0;
+
+        for height in [1.0, jump.max_height() / 2.0, jump.max_height() - 1.0] {
+            let (rises, falls) = jump.time_above(height).unwrap();
+            assert!(rises < apex && apex < falls);
+            assert_near(apex - rises, falls - apex);
+        }
+    }
+
+    #[test]
+    fn roots_meet_at_the_max_height() {
+        let jump = JumpConfig::new(SCREEN_UNIT);
+
+        let (rises, falls) = jump.time_above(jump.max_height()).unwrap();
+        assert_near(rises, jump.airtime() / 2.0);
+        assert_near(falls, jump.airtime() / 2.0);
+
+        assert_eq!(jump.time_above(jump.max_height() + 0.1), None);
+    }
+
+    #[test]
+    fn max_height_scales_with_the_screen_unit() {
+        assert_near(
+            JumpConfig::new(2.0 * SCREEN_UNIT).max_height(),
+            2.0 * JumpConfig::new(SCREEN_UNIT).max_height(),
+        );
+    }
+}
@@ -1 +1,23 @@
+pub mod actions;
+pub mod aseprite;
+pub mod cleanup;
+pub mod collision;
+pub mod combo;
+pub mod difficulty;
+pub mod gltf_animation;
+pub mod gltf_hooks;
+pub mod health;
+pub mod high_scores;
 pub mod jump;
+pub mod model_viewer;
+pub mod movement;
+pub mod parallax;
+pub mod pool;
+pub mod replay;
+pub mod rng;
+pub mod ron_asset;
+pub mod scoring;
+pub mod scroll;
+pub mod spawner;
+pub mod sprite_animation;
+pub mod virtual_controls;
@@ -1,3 +1,446 @@
-fn main() {
-    println!("Hello, world!");
+// Validates the assets folder without opening a window or touching the GPU, for CI use.
+//
+// cargo run -- [--strict] [assets folder] [source folders...]
+//
+// Errors (non-zero exit code): asset paths found in the sources that do not exist, glTF files
+// that fail to parse or point to missing buffers and images, unreadable images, RON files that do
+// not parse, images and Aseprite files that Bevy's own loaders reject, Git LFS pointers that were
+// never pulled. Fonts and sounds are only checked to exist.
+// Warnings (non-zero exit code with --strict): atlas images whose sizes are not powers of two,
+// which breaks mipmapping and atlas padding on some targets, optional assets that are missing,
+// and assets nothing refers to.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bevy::app::App;
+use bevy::asset::{AssetApp, AssetPlugin, AssetServer, LoadState, UntypedHandle};
+use bevy::image::{CompressedImageFormats, Image, ImageLoader};
+use bevy::sprite::TextureAtlasLayout;
+use bevy::MinimalPlugins;
+
+use bevy_game_bits::aseprite::{Aseprite, AsepritePlugin};
+
+const DEFAULT_ASSETS_FOLDER: &str = "assets";
+const DEFAULT_SOURCE_FOLDERS: [&str; 2] = ["src", "examples"];
+
+const ASSET_EXTENSIONS: [&str; 14] = [
+    "png", "jpg", "jpeg", "ktx2", "glb", "gltf", "bin", "ttf", "otf", "wav", "ogg", "ron", "ase",
+    "aseprite",
+];
+// Only what the `image` crate is built to decode.
+const IMAGE_EXTENSIONS: [&str; 1] = ["png"];
+// Loaded through Bevy, as the game would. Fonts, sounds and glTF scenes are not, as their plugins
+// pull in rendering or an audio output: they are only checked to exist, glTF files being parsed
+// on their own.
+const LOADER_EXTENSIONS: [&str; 4] = ["png", "ktx2", "ase", "aseprite"];
+// For all the headless loads together, not per asset.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Folders referenced, but not shipped in this repository. The game runs without them.
+const OPTIONAL_ASSETS: [(&str, &str); 1] = [(
+    "environment_maps/",
+    "copy the model viewer environment maps from the Bevy repository assets",
+)];
+
+// Sources mentioning this load the images they refer to as atlases.
+const ATLAS_MARKER: &str = "TextureAtlas";
+
+const LFS_POINTER_HEADER: &[u8] = b"version https://git-lfs";
+
+// Files that document the assets rather than being loaded.
+const DOCUMENTATION_MARKERS: [&str; 3] = ["LICENSE", "CREDITS", "README"];
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Default)]
+struct Report {
+    issues: Vec<(Severity, String, String)>,
+}
+
+impl Report {
+    fn error(&mut self, location: impl Into<String>, message: impl Into<String>) {
+        self.issues
+            .push((Severity::Error, location.into(), message.into()));
+    }
+
+    fn warning(&mut self, location: impl Into<String>, message: impl Into<String>) {
+        self.issues
+            .push((Severity::Warning, location.into(), message.into()));
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.issues
+            .iter()
+            .filter(|(s, _, _)| *s == severity)
+            .count()
+    }
+}
+
+fn main() -> ExitCode {
+    let mut strict = false;
+    let mut folders: Vec<String> = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--strict" => strict = true,
+            _ => folders.push(arg),
+        }
+    }
+
+    let assets_folder = PathBuf::from(
+        folders
+            .first()
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_ASSETS_FOLDER),
+    );
+    let source_folders: Vec<PathBuf> = if folders.len() > 1 {
+        folders[1..].iter().map(PathBuf::from).collect()
+    } else {
+        DEFAULT_SOURCE_FOLDERS.iter().map(PathBuf::from).collect()
+    };
+
+    let mut report = Report::default();
+    let mut referenced: BTreeSet<String> = BTreeSet::new();
+    let mut atlases: BTreeSet<String> = BTreeSet::new();
+
+    let assets = list_files(&assets_folder);
+    if assets.is_empty() {
+        report.error(assets_folder.display().to_string(), "no assets found");
+    }
+
+    for source in source_folders.iter().flat_map(|folder| list_files(folder)) {
+        if source
+            .extension()
+            .is_some_and(|extension| extension == "rs")
+        {
+            check_source(
+                &source,
+                &assets_folder,
+                &mut referenced,
+                &mut atlases,
+                &mut report,
+            );
+        }
+    }
+
+    let mut loadable = Vec::new();
+
+    for asset in assets.iter() {
+        let name = asset_name(asset, &assets_folder);
+
+        if is_lfs_pointer(asset) {
+            report.error(name, "is a Git LFS pointer, run `git lfs pull`");
+            continue;
+        }
+
+        let extension = extension(asset);
+
+        match extension.as_str() {
+            "glb" | "gltf" => check_gltf(asset, &assets_folder, &mut referenced, &mut report),
+            "ron" => {
+                check_ron(asset, &name, &mut report);
+                // Data files refer to other assets the same way sources do.
+                check_source(
+                    asset,
+                    &assets_folder,
+                    &mut referenced,
+                    &mut atlases,
+                    &mut report,
+                );
+            }
+            // Unreadable images are already reported, the loader would only say it again.
+            extension
+                if IMAGE_EXTENSIONS.contains(&extension)
+                    && !check_image(asset, &name, atlases.contains(&name), &mut report) =>
+            {
+                continue;
+            }
+            _ => {}
+        }
+
+        if LOADER_EXTENSIONS.contains(&extension.as_str()) {
+            loadable.push(name);
+        }
+    }
+
+    check_loaders(&assets_folder, &loadable, &mut report);
+
+    // glTF dependencies are only known once every glTF file has been checked.
+    for asset in assets.iter() {
+        let name = asset_name(asset, &assets_folder);
+
+        let documentation = DOCUMENTATION_MARKERS
+            .iter()
+            .any(|marker| name.to_uppercase().contains(marker));
+
+        if !documentation && !referenced.contains(&name) {
+            report.warning(name, "not referenced by any source or glTF file");
+        }
+    }
+
+    report.issues.sort();
+    for (severity, location, message) in report.issues.iter() {
+        let label = match severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        println!("{}: {}: {}", label, location, message);
+    }
+
+    let errors = report.count(Severity::Error);
+    let warnings = report.count(Severity::Warning);
+    println!(
+        "{} assets checked, {} errors, {} warnings",
+        assets.len(),
+        errors,
+        warnings
+    );
+
+    if errors > 0 || (strict && warnings > 0) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn list_files(folder: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    let Ok(entries) = fs::read_dir(folder) else {
+        return files;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(list_files(&path));
+        } else {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    files
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+// Binary assets are stored with Git LFS, a checkout without it only has small text pointers.
+fn is_lfs_pointer(path: &Path) -> bool {
+    let mut header = [0u8; LFS_POINTER_HEADER.len()];
+
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|()| header == LFS_POINTER_HEADER)
+}
+
+// Path relative to the assets folder, with forward slashes, as given to the `AssetServer`.
+fn asset_name(path: &Path, assets_folder: &Path) -> String {
+    path.strip_prefix(assets_folder)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// Any string literal ending with an asset extension is taken for an asset path. Labels such as
+// `#Scene0` are dropped.
+fn check_source(
+    source: &Path,
+    assets_folder: &Path,
+    referenced: &mut BTreeSet<String>,
+    atlases: &mut BTreeSet<String>,
+    report: &mut Report,
+) {
+    let Ok(content) = fs::read_to_string(source) else {
+        report.error(source.display().to_string(), "could not read source file");
+        return;
+    };
+
+    let uses_atlases = content.contains(ATLAS_MARKER);
+
+    for (line_number, line) in content.lines().enumerate() {
+        for literal in line.split('"').skip(1).step_by(2) {
+            let path = literal.split('#').next().unwrap_or(literal);
+
+            let is_asset = path.contains('/')
+                && !path.contains(' ')
+                && ASSET_EXTENSIONS.contains(&extension(Path::new(path)).as_str());
+
+            if !is_asset {
+                continue;
+            }
+
+            let location = format!("{}:{}", source.display(), line_number + 1);
+            let optional = OPTIONAL_ASSETS
+                .iter()
+                .find(|(folder, _)| path.starts_with(folder));
+
+            match optional {
+                _ if assets_folder.join(path).is_file() => {}
+                Some((_, hint)) => report.warning(
+                    location,
+                    format!("missing optional asset {}, {}", path, hint),
+                ),
+                None => report.error(location, format!("missing asset {}", path)),
+            }
+
+            referenced.insert(path.to_string());
+            if uses_atlases {
+                atlases.insert(path.to_string());
+            }
+        }
+    }
+}
+
+fn check_gltf(
+    path: &Path,
+    assets_folder: &Path,
+    referenced: &mut BTreeSet<String>,
+    report: &mut Report,
+) {
+    let name = asset_name(path, assets_folder);
+
+    let gltf = match fs::read(path)
+        .map_err(|error| error.to_string())
+        .and_then(|bytes| gltf::Gltf::from_slice(&bytes).map_err(|error| error.to_string()))
+    {
+        Ok(gltf) => gltf,
+        Err(error) => {
+            report.error(name, format!("failed to parse glTF: {}", error));
+            return;
+        }
+    };
+
+    let buffer_uris = gltf.buffers().filter_map(|buffer| match buffer.source() {
+        gltf::buffer::Source::Uri(uri) => Some(uri),
+        gltf::buffer::Source::Bin => None,
+    });
+    let image_uris = gltf.images().filter_map(|image| match image.source() {
+        gltf::image::Source::Uri { uri, .. } => Some(uri),
+        gltf::image::Source::View { .. } => None,
+    });
+
+    let folder = path.parent().unwrap_or(assets_folder);
+
+    for uri in buffer_uris.chain(image_uris) {
+        if uri.starts_with("data:") {
+            continue;
+        }
+
+        let dependency = folder.join(uri);
+        if dependency.is_file() {
+            referenced.insert(asset_name(&dependency, assets_folder));
+        } else {
+            report.error(name.clone(), format!("missing glTF dependency {}", uri));
+        }
+    }
+}
+
+// Only atlases need power of two sizes, backgrounds and UI images can be any size. Returns
+// whether the image could be read.
+fn check_image(path: &Path, name: &str, atlas: bool, report: &mut Report) -> bool {
+    match image::image_dimensions(path) {
+        Ok((width, height)) => {
+            if atlas && (!width.is_power_of_two() || !height.is_power_of_two()) {
+                report.warning(name, format!("{}x{} is not a power of two", width, height));
+            }
+            true
+        }
+        Err(error) => {
+            report.error(name, format!("failed to read image: {}", error));
+            false
+        }
+    }
+}
+
+// Syntax only, the types they deserialize into live with the games.
+fn check_ron(path: &Path, name: &str, report: &mut Report) {
+    let result = fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|content| {
+            ron::from_str::<ron::Value>(&content).map_err(|error| error.to_string())
+        });
+
+    if let Err(error) = result {
+        report.error(name, format!("failed to parse RON: {}", error));
+    }
+}
+
+// Runs the assets through Bevy's loaders in a headless app: no window, no GPU.
+fn check_loaders(assets_folder: &Path, names: &[String], report: &mut Report) {
+    if names.is_empty() {
+        return;
+    }
+
+    // The asset plugin resolves relative paths from the crate, not from where it runs.
+    let root = fs::canonicalize(assets_folder).unwrap_or_else(|_| assets_folder.to_path_buf());
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin {
+            file_path: root.display().to_string(),
+            ..Default::default()
+        },
+        AsepritePlugin,
+    ))
+    .init_asset::<Image>()
+    .init_asset::<TextureAtlasLayout>()
+    .register_asset_loader(ImageLoader::new(CompressedImageFormats::all()));
+    app.finish();
+    app.cleanup();
+
+    // Handles are kept, as dropping them cancels the load.
+    let mut pending: Vec<(&String, UntypedHandle)> = {
+        let asset_server = app.world().resource::<AssetServer>();
+
+        names
+            .iter()
+            .map(|name| {
+                let handle = match extension(Path::new(name)).as_str() {
+                    "ase" | "aseprite" => asset_server.load::<Aseprite>(name).untyped(),
+                    _ => asset_server.load::<Image>(name).untyped(),
+                };
+                (name, handle)
+            })
+            .collect()
+    };
+
+    let started = Instant::now();
+
+    while !pending.is_empty() && started.elapsed() < LOAD_TIMEOUT {
+        app.update();
+
+        let asset_server = app.world().resource::<AssetServer>();
+        pending.retain(|(name, handle)| match asset_server.get_load_state(handle) {
+            Some(LoadState::Loaded) => false,
+            Some(LoadState::Failed(error)) => {
+                report.error(name.as_str(), format!("failed to load: {}", error));
+                false
+            }
+            _ => true,
+        });
+
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    for (name, _) in pending {
+        report.error(name.as_str(), "timed out while loading");
+    }
 }
@@ -0,0 +1,296 @@
+use std::f32::consts::*;
+use std::time::Duration;
+
+use bevy::input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit};
+use bevy::pbr::{CascadeShadowConfig, CascadeShadowConfigBuilder, DirectionalLightShadowMap};
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+
+use crate::gltf_animation::{GltfAnimationPlugin, GltfAnimations};
+use crate::gltf_hooks::MapGltfNodes;
+
+const ORBIT_SPEED: f32 = 0.005;
+const PAN_SPEED: f32 = 0.001;
+const ZOOM_SPEED: f32 = 0.1;
+const MIN_RADIUS: f32 = 0.01;
+const FRAMING_MARGIN: f32 = 1.2;
+const CROSSFADE_DURATION: Duration = Duration::from_millis(250);
+
+// Previews a glTF file: left drag orbits, right drag pans, the wheel zooms.
+// F frames the model again, L toggles the light animation and N plays the next animation clip.
+pub struct ModelViewerPlugin {
+    pub model: String,
+}
+
+impl Plugin for ModelViewerPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<GltfAnimationPlugin>() {
+            app.add_plugins(GltfAnimationPlugin);
+        }
+
+        app.insert_resource(ViewedModelPath(self.model.clone()))
+            .insert_resource(DirectionalLightShadowMap { size: 4096 })
+            .add_systems(Startup, spawn_model_viewer)
+            .add_systems(
+                Update,
+                (
+                    frame_model,
+                    orbit_camera,
+                    update_orbit_camera_transform,
+                    toggle_light_animation,
+                    animate_light_direction,
+                    cycle_animations,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct ViewedModelPath(pub String);
+
+#[derive(Component)]
+pub struct ViewedModel;
+
+#[derive(Component)]
+pub struct OrbitCamera {
+    pub focus: Vec3,
+    pub radius: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    framed: bool,
+}
+
+impl OrbitCamera {
+    pub fn looking_at(eye: Vec3, focus: Vec3) -> Self {
+        let offset = eye - focus;
+        let radius = offset.length();
+
+        Self {
+            focus,
+            radius,
+            yaw: offset.x.atan2(offset.z),
+            pitch: -(offset.y / radius).asin(),
+            framed: false,
+        }
+    }
+
+    pub fn frame(&mut self) {
+        self.framed = false;
+    }
+
+    pub fn transform(&self) -> Transform {
+        let rotation = Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0);
+
+        Transform {
+            translation: self.focus + rotation * Vec3::Z * self.radius,
+            rotation,
+            ..default()
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ViewerLight {
+    pub animated: bool,
+    angle: f32,
+}
+
+pub fn spawn_model_viewer(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    model: Res<ViewedModelPath>,
+) {
+    let orbit = OrbitCamera::looking_at(Vec3::new(0.7, 0.7, 1.0), Vec3::new(0.0, 0.3, 0.0));
+
+    commands.spawn((
+        Camera3d::default(),
+        orbit.transform(),
+        orbit,
+        EnvironmentMapLight {
+            diffuse_map: asset_server.load("environment_maps/pisa_diffuse_rgb9e5_zstd.ktx2"),
+            specular_map: asset_server.load("environment_maps/pisa_specular_rgb9e5_zstd.ktx2"),
+            intensity: 250.0,
+            ..default()
+        },
+    ));
+
+    commands.spawn((
+        ViewerLight {
+            animated: true,
+            angle: 0.0,
+        },
+        DirectionalLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        // Cascade bounds are fitted to the model once it is framed.
+        CascadeShadowConfigBuilder {
+            num_cascades: 1,
+            maximum_distance: 1.6,
+            ..default()
+        }
+        .build(),
+    ));
+
+    commands.spawn((
+        ViewedModel,
+        SceneRoot(asset_server.load(GltfAssetLabel::Scene(0).from_asset(model.0.clone()))),
+        GltfAnimations::new(asset_server.load(model.0.clone())),
+        MapGltfNodes,
+    ));
+}
+
+// Bounds only exist once meshes are spawned and their `Aabb` computed, so this keeps trying
+// until they are.
+pub fn frame_model(
+    mut cameras: Query<(&mut OrbitCamera, &Projection)>,
+    mut lights: Query<&mut CascadeShadowConfig, With<ViewerLight>>,
+    models: Query<Entity, With<ViewedModel>>,
+    children: Query<&Children>,
+    bounds: Query<(&Aabb, &GlobalTransform)>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+) {
+    for (mut orbit, projection) in cameras.iter_mut() {
+        if keyboard.just_pressed(KeyCode::KeyF) {
+            orbit.frame();
+        }
+
+        if orbit.framed {
+            continue;
+        }
+
+        let mut min = Vec3::MAX;
+        let mut max = Vec3::MIN;
+
+        for model in models.iter() {
+            for entity in children.iter_descendants(model) {
+                let Ok((aabb, transform)) = bounds.get(entity) else {
+                    continue;
+                };
+
+                let affine = transform.affine();
+                let center = Vec3::from(affine.transform_point3a(aabb.center));
+                let half_extents = Vec3::from(affine.matrix3.abs() * aabb.half_extents);
+
+                min = min.min(center - half_extents);
+                max = max.max(center + half_extents);
+            }
+        }
+
+        if min.cmpgt(max).any() {
+            continue;
+        }
+
+        let sphere_radius = ((max - min).length() / 2.0).max(MIN_RADIUS);
+        let fov = match projection {
+            Projection::Perspective(perspective) => perspective.fov,
+            _ => FRAC_PI_4,
+        };
+
+        orbit.focus = (min + max) / 2.0;
+        orbit.radius = sphere_radius / (fov / 2.0).sin() * FRAMING_MARGIN;
+        orbit.framed = true;
+
+        for mut cascade in lights.iter_mut() {
+            *cascade = CascadeShadowConfigBuilder {
+                num_cascades: 1,
+                maximum_distance: orbit.radius + sphere_radius,
+                ..default()
+            }
+            .build();
+        }
+    }
+}
+
+pub fn orbit_camera(
+    mut cameras: Query<&mut OrbitCamera>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    motion: Res<AccumulatedMouseMotion>,
+    scroll: Res<AccumulatedMouseScroll>,
+) {
+    let zoom = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / 100.0,
+    };
+
+    for mut orbit in cameras.iter_mut() {
+        if mouse.pressed(MouseButton::Left) {
+            orbit.yaw -= motion.delta.x * ORBIT_SPEED;
+            orbit.pitch = (orbit.pitch - motion.delta.y * ORBIT_SPEED)
+                .clamp(-FRAC_PI_2 + 0.01, FRAC_PI_2 - 0.01);
+        }
+
+        if mouse.pressed(MouseButton::Right) || mouse.pressed(MouseButton::Middle) {
+            let rotation = orbit.transform().rotation;
+            let pan = rotation * Vec3::new(-motion.delta.x, motion.delta.y, 0.0);
+            let radius = orbit.radius;
+            orbit.focus += pan * radius * PAN_SPEED;
+        }
+
+        if zoom != 0.0 {
+            orbit.radius = (orbit.radius * (1.0 - zoom * ZOOM_SPEED)).max(MIN_RADIUS);
+        }
+    }
+}
+
+pub fn update_orbit_camera_transform(
+    mut cameras: Query<(&OrbitCamera, &mut Transform), Changed<OrbitCamera>>,
+) {
+    for (orbit, mut transform) in cameras.iter_mut() {
+        *transform = orbit.transform();
+    }
+}
+
+pub fn toggle_light_animation(
+    mut lights: Query<&mut ViewerLight>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyL) {
+        return;
+    }
+
+    for mut light in lights.iter_mut() {
+        light.animated = !light.animated;
+    }
+}
+
+pub fn animate_light_direction(
+    time: Res<Time>,
+    mut query: Query<(&mut ViewerLight, &mut Transform)>,
+) {
+    for (mut light, mut transform) in &mut query {
+        if light.animated {
+            light.angle += time.delta_secs() * PI / 5.0;
+        }
+
+        transform.rotation = Quat::from_euler(EulerRot::ZYX, 0.0, light.angle, -FRAC_PI_4);
+    }
+}
+
+// Plays the first clip of the model, if any, and crossfades to the next one on demand.
+pub fn cycle_animations(
+    mut query: Query<&mut GltfAnimations, With<ViewedModel>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+) {
+    for mut animations in &mut query {
+        if !animations.is_ready() {
+            continue;
+        }
+
+        let names = animations.clip_names();
+
+        let next = match animations.current() {
+            None => names.first(),
+            Some(current) if keyboard.just_pressed(KeyCode::KeyN) => {
+                let index = names.iter().position(|name| *name == current).unwrap_or(0);
+                names.get((index + 1) % names.len())
+            }
+            _ => None,
+        };
+
+        if let Some(next) = next.map(|name| name.to_string()) {
+            animations.crossfade(&next, CROSSFADE_DURATION);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use bevy::prelude::*;
+
+use crate::actions::{Action, ActionBindings, ActionsPlugin};
+use crate::virtual_controls::VirtualJoystick;
+
+// Past this, the stick would have no range left to reach full speed.
+const MAX_DEADZONE: f32 = 0.99;
+
+pub struct TopDownMovementPlugin;
+
+impl Plugin for TopDownMovementPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ActionsPlugin>() {
+            app.add_plugins(ActionsPlugin);
+        }
+
+        app.add_systems(
+            Update,
+            (read_movement_axis, apply_top_down_movement).chain(),
+        );
+    }
+}
+
+// Raw 2D input, before deadzone and response curve. Written by `read_movement_axis`, but can
+// also be driven by an AI or a replay.
+#[derive(Component, Default, Debug)]
+pub struct MovementAxis(pub Vec2);
+
+#[derive(Component, Debug)]
+#[require(MovementAxis)]
+pub struct TopDownMovement {
+    // Units per second, reached with the stick fully tilted.
+    pub max_speed: f32,
+    // Units per second², when speeding up and when slowing down.
+    pub acceleration: f32,
+    pub deceleration: f32,
+    // Radial deadzone, as a fraction of the stick range. Clamped to [0, 0.99].
+    pub deadzone: f32,
+    // Maps the stick tilt (past the deadzone) to a fraction of `max_speed`.
+    pub response: EaseFunction,
+    velocity: Vec2,
+}
+
+impl TopDownMovement {
+    pub fn new(max_speed: f32) -> Self {
+        Self {
+            max_speed,
+            acceleration: max_speed * 8.0,
+            deceleration: max_speed * 10.0,
+            deadzone: 0.15,
+            response: EaseFunction::Linear,
+            velocity: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_deadzone(mut self, deadzone: f32) -> Self {
+        self.deadzone = deadzone.clamp(0.0, MAX_DEADZONE);
+        self
+    }
+
+    pub fn with_response(mut self, response: EaseFunction) -> Self {
+        self.response = response;
+        self
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity
+    }
+
+    pub fn target_velocity(&self, axis: Vec2) -> Vec2 {
+        let length = axis.length();
+        // The field may have been set directly.
+        let deadzone = self.deadzone.clamp(0.0, MAX_DEADZONE);
+
+        if length <= deadzone {
+            return Vec2::ZERO;
+        }
+
+        // Rescale past the deadzone so that speed starts from zero instead of jumping, and keep
+        // diagonals as fast as straight lines.
+        let tilt = ((length - deadzone) / (1.0 - deadzone)).min(1.0);
+        let speed = EasingCurve::new(0.0, 1.0, self.response).sample_clamped(tilt);
+
+        axis / length * speed * self.max_speed
+    }
+}
+
+pub fn keyboard_axis(bindings: &ActionBindings, keyboard: &ButtonInput<KeyCode>) -> Vec2 {
+    let mut axis = Vec2::ZERO;
+
+    for (key_code, action) in bindings.keys.iter() {
+        if !keyboard.pressed(*key_code) {
+            continue;
+        }
+
+        match action {
+            Action::Left => axis.x -= 1.0,
+            Action::Right => axis.x += 1.0,
+            Action::Up => axis.y += 1.0,
+            Action::Down => axis.y -= 1.0,
+            _ => {}
+        }
+    }
+
+    axis.clamp(Vec2::NEG_ONE, Vec2::ONE)
+}
+
+// Keyboard, gamepad sticks and virtual joysticks all feed the same axis, the most tilted one
+// wins.
+pub fn read_movement_axis(
+    mut query: Query<&mut MovementAxis, With<TopDownMovement>>,
+    bindings: Res<ActionBindings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    joysticks: Query<&VirtualJoystick>,
+) {
+    let axis = std::iter::once(keyboard_axis(&bindings, &keyboard))
+        .chain(gamepads.iter().map(|gamepad| gamepad.left_stick()))
+        .chain(joysticks.iter().map(|joystick| joystick.value()))
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .unwrap_or_default();
+
+    for mut movement_axis in query.iter_mut() {
+        movement_axis.0 = axis;
+    }
+}
+
+pub fn apply_top_down_movement(
+    mut query: Query<(&mut TopDownMovement, &MovementAxis, &mut Transform)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+
+    for (mut movement, axis, mut transform) in query.iter_mut() {
+        let target = movement.target_velocity(axis.0);
+
+        let rate = if target.length_squared() >= movement.velocity.length_squared() {
+            movement.acceleration
+        } else {
+            movement.deceleration
+        };
+
+        let velocity = movement.velocity;
+        let delta = (target - velocity).clamp_length_max(rate * dt);
+        movement.velocity = velocity + delta;
+
+        transform.translation += movement.velocity.extend(0.0) * dt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_inside_the_deadzone() {
+        let movement = TopDownMovement::new(100.0).with_deadzone(0.2);
+
+        assert_eq!(movement.target_velocity(Vec2::ZERO), Vec2::ZERO);
+        assert_eq!(movement.target_velocity(Vec2::new(0.1, 0.1)), Vec2::ZERO);
+        assert_eq!(movement.target_velocity(Vec2::new(0.0, -0.2)), Vec2::ZERO);
+    }
+
+    #[test]
+    fn full_tilt_reaches_max_speed() {
+        let movement = TopDownMovement::new(100.0);
+
+        let velocity = movement.target_velocity(Vec2::X);
+        assert!((velocity - Vec2::new(100.0, 0.0)).length() < 1e-3);
+    }
+
+    #[test]
+    fn diagonals_are_not_faster() {
+        let movement = TopDownMovement::new(100.0);
+
+        // Both keys held, as `keyboard_axis` does not normalize.
+        let diagonal = movement.target_velocity(Vec2::ONE);
+        let straight = movement.target_velocity(Vec2::Y);
+
+        assert!((diagonal.length() - straight.length()).abs() < 1e-3);
+        assert!((diagonal.x - diagonal.y).abs() < 1e-3);
+    }
+
+    #[test]
+    fn speed_starts_from_zero_past_the_deadzone() {
+        let movement = TopDownMovement::new(100.0).with_deadzone(0.5);
+
+        let slow = movement.target_velocity(Vec2::new(0.51, 0.0)).length();
+        let half = movement.target_velocity(Vec2::new(0.75, 0.0)).length();
+
+        assert!(slow < 5.0);
+        assert!((half - 50.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn deadzone_is_clamped() {
+        let movement = TopDownMovement::new(100.0).with_deadzone(1.5);
+        assert!(movement.deadzone < 1.0);
+        assert!(movement.target_velocity(Vec2::X).is_finite());
+
+        // Set directly, past the full range.
+        let mut movement = TopDownMovement::new(100.0);
+        movement.deadzone = 1.0;
+
+        let velocity = movement.target_velocity(Vec2::ONE);
+        assert!(velocity.is_finite());
+        assert!((velocity.length() - 100.0).abs() < 1e-3);
+
+        movement.deadzone = -1.0;
+        assert_eq!(movement.target_velocity(Vec2::ZERO), Vec2::ZERO);
+    }
+}
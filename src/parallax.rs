@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+
+use crate::scroll::{ScrollPlugin, ScrollSet, WorldScroll};
+
+// Endless backgrounds: each layer tiles its sprite image horizontally and scrolls at a fraction
+// of the world speed, wrapping around by a whole tile so that the seam never shows.
+pub struct ParallaxPlugin;
+
+impl Plugin for ParallaxPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ScrollPlugin>() {
+            app.add_plugins(ScrollPlugin);
+        }
+
+        app.add_systems(FixedUpdate, scroll_parallax_layers.in_set(ScrollSet));
+    }
+}
+
+// Needs a `Sprite` with an image. Its size is taken over, and so is the translation, apart from
+// the depth.
+#[derive(Component, Debug)]
+#[require(Sprite)]
+pub struct ParallaxLayer {
+    // Fraction of the world speed, 1.0 for the ground the player runs on, less further away.
+    pub depth: f32,
+    // Width to cover, centered on X = 0.
+    pub width: f32,
+    // Y of the top edge, so that images of any height line up.
+    pub top: f32,
+    scrolled: f32,
+}
+
+impl ParallaxLayer {
+    pub fn new(depth: f32, width: f32, top: f32) -> Self {
+        Self {
+            depth,
+            width,
+            top,
+            scrolled: 0.0,
+        }
+    }
+}
+
+pub fn spawn_parallax_layer(
+    commands: &mut Commands,
+    image: Handle<Image>,
+    layer: ParallaxLayer,
+    z: f32,
+) -> Entity {
+    commands
+        .spawn((
+            layer,
+            Sprite {
+                image,
+                image_mode: SpriteImageMode::Tiled {
+                    tile_x: true,
+                    tile_y: false,
+                    stretch_value: 1.0,
+                },
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, z),
+        ))
+        .id()
+}
+
+pub fn scroll_parallax_layers(
+    mut query: Query<(&mut ParallaxLayer, &mut Sprite, &mut Transform)>,
+    images: Res<Assets<Image>>,
+    world_scroll: Res<WorldScroll>,
+    time: Res<Time>,
+) {
+    let distance = world_scroll.speed() * time.delta_secs();
+
+    for (mut layer, mut sprite, mut transform) in query.iter_mut() {
+        // Tiles are only known once the image is loaded.
+        let Some(tile) = images.get(&sprite.image).map(Image::size_f32) else {
+            continue;
+        };
+
+        layer.scrolled = (layer.scrolled + distance * layer.depth).rem_euclid(tile.x);
+
+        // One extra tile, so that the layer still covers the width once shifted by up to a tile.
+        let size = Some(Vec2::new(layer.width + tile.x, tile.y));
+        if sprite.custom_size != size {
+            sprite.custom_size = size;
+        }
+
+        transform.translation.x = tile.x / 2.0 - layer.scrolled;
+        transform.translation.y = layer.top - tile.y / 2.0;
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+// Reuses what games spawn over and over: asset handles shared by name, and entities released to
+// a pool instead of being despawned, for spawners to pick up again.
+pub struct PoolPlugin;
+
+impl Plugin for PoolPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EntityPool>();
+    }
+}
+
+// Assets built in code, such as meshes and materials, added once per key. Add one per asset
+// type with `init_resource::<HandleCache<Mesh>>()`.
+#[derive(Resource)]
+pub struct HandleCache<A: Asset> {
+    handles: HashMap<String, Handle<A>>,
+}
+
+impl<A: Asset> Default for HandleCache<A> {
+    fn default() -> Self {
+        Self {
+            handles: HashMap::new(),
+        }
+    }
+}
+
+impl<A: Asset> HandleCache<A> {
+    // `make` only runs the first time a key is asked for.
+    pub fn get_or_add(
+        &mut self,
+        key: &str,
+        assets: &mut Assets<A>,
+        make: impl FnOnce() -> A,
+    ) -> Handle<A> {
+        self.handles
+            .entry(key.to_string())
+            .or_insert_with(|| assets.add(make()))
+            .clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+}
+
+// Names the pool an entity goes back to when released.
+#[derive(Component, Debug, Clone)]
+pub struct Pooled(pub String);
+
+// Released entities, by pool name. They keep their `Pooled`, `Transform` and `Visibility`, and
+// nothing else, so that game systems no longer see them.
+#[derive(Resource, Debug, Default)]
+pub struct EntityPool {
+    free: HashMap<String, Vec<Entity>>,
+}
+
+impl EntityPool {
+    // Released entities ready to be reused.
+    pub fn len(&self, pool: &str) -> usize {
+        self.free.get(pool).map_or(0, Vec::len)
+    }
+
+    // `is_pooled` filters out entities despawned since they were released.
+    pub fn take(&mut self, pool: &str, is_pooled: impl Fn(Entity) -> bool) -> Option<Entity> {
+        let free = self.free.get_mut(pool)?;
+
+        while let Some(entity) = free.pop() {
+            if is_pooled(entity) {
+                return Some(entity);
+            }
+        }
+
+        None
+    }
+}
+
+pub trait ReleaseExt {
+    // Puts a `Pooled` entity back into its pool, despawns any other. Pooled entities must not
+    // have children.
+    fn release(&mut self);
+}
+
+impl ReleaseExt for EntityCommands<'_> {
+    fn release(&mut self) {
+        self.queue(|mut entity: EntityWorldMut| {
+            let Some(Pooled(pool)) = entity.get::<Pooled>().cloned() else {
+                entity.despawn();
+                return;
+            };
+
+            let id = entity.id();
+            entity
+                .retain::<(Pooled, Transform, Visibility)>()
+                .insert(Visibility::Hidden);
+
+            entity.world_scope(|world| {
+                let mut pools = world.get_resource_or_init::<EntityPool>();
+                let free = pools.free.entry(pool).or_default();
+
+                // Released twice in the same frame.
+                if !free.contains(&id) {
+                    free.push(id);
+                }
+            });
+        });
+    }
+}
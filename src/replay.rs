@@ -0,0 +1,295 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::actions::{ActionMask, Actions, ActionsPlugin, ActionsSet};
+
+const MAGIC: &[u8; 4] = b"BGBR";
+const VERSION: u8 = 2;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ActionsPlugin>() {
+            app.add_plugins(ActionsPlugin);
+        }
+
+        app.init_resource::<InputRecorder>()
+            .add_event::<ReplayFinished>()
+            .add_systems(
+                FixedPreUpdate,
+                (
+                    play_back_actions.in_set(ActionsSet::Override),
+                    record_actions.after(ActionsSet::Commit),
+                ),
+            );
+    }
+}
+
+#[derive(Event)]
+pub struct ReplayFinished;
+
+// Per-tick action masks, stored as (mask, repeat count) runs: holding nothing or holding a
+// key for a while costs a single run.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct InputRecording {
+    // The `GameRng` seed the run started from.
+    pub seed: u64,
+    runs: Vec<(ActionMask, u32)>,
+}
+
+impl InputRecording {
+    pub fn push(&mut self, mask: ActionMask) {
+        match self.runs.last_mut() {
+            Some((last_mask, count)) if *last_mask == mask && *count < u32::MAX => *count += 1,
+            _ => self.runs.push((mask, 1)),
+        }
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.runs.iter().map(|(_, count)| *count as u64).sum()
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+        writer.write_all(&(self.runs.len() as u32).to_le_bytes())?;
+
+        for (mask, count) in self.runs.iter() {
+            writer.write_all(&mask.to_le_bytes())?;
+            writer.write_all(&count.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header)?;
+
+        if &header[0..4] != MAGIC || header[4] != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an input recording",
+            ));
+        }
+
+        let mut seed = [0u8; 8];
+        reader.read_exact(&mut seed)?;
+
+        let mut len = [0u8; 4];
+        reader.read_exact(&mut len)?;
+
+        let mut runs = Vec::new();
+        for _ in 0..u32::from_le_bytes(len) {
+            let mut mask = [0u8; 2];
+            let mut count = [0u8; 4];
+            reader.read_exact(&mut mask)?;
+            reader.read_exact(&mut count)?;
+
+            // Playback would still spend a tick on it.
+            let count = u32::from_le_bytes(count);
+            if count == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "empty run in input recording",
+                ));
+            }
+
+            runs.push((ActionMask::from_le_bytes(mask), count));
+        }
+
+        Ok(Self {
+            seed: u64::from_le_bytes(seed),
+            runs,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+#[derive(Default)]
+enum RecorderState {
+    #[default]
+    Idle,
+    Recording(InputRecording),
+    Playing {
+        recording: InputRecording,
+        run: usize,
+        tick_in_run: u32,
+    },
+}
+
+#[derive(Resource, Default)]
+pub struct InputRecorder {
+    state: RecorderState,
+}
+
+impl InputRecorder {
+    pub fn start_recording(&mut self, seed: u64) {
+        self.state = RecorderState::Recording(InputRecording { seed, ..default() });
+    }
+
+    pub fn start_playback(&mut self, recording: InputRecording) {
+        self.state = RecorderState::Playing {
+            recording,
+            run: 0,
+            tick_in_run: 0,
+        };
+    }
+
+    // Returns the recording in progress, if any.
+    pub fn stop(&mut self) -> Option<InputRecording> {
+        match std::mem::take(&mut self.state) {
+            RecorderState::Recording(recording) => Some(recording),
+            _ => None,
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        matches!(self.state, RecorderState::Recording(_))
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self.state, RecorderState::Playing { .. })
+    }
+}
+
+pub fn record_actions(mut recorder: ResMut<InputRecorder>, actions: Res<Actions>) {
+    if let RecorderState::Recording(recording) = &mut recorder.state {
+        recording.push(actions.mask());
+    }
+}
+
+pub fn play_back_actions(
+    mut recorder: ResMut<InputRecorder>,
+    mut actions: ResMut<Actions>,
+    mut events: EventWriter<ReplayFinished>,
+) {
+    let RecorderState::Playing {
+        recording,
+        run,
+        tick_in_run,
+    } = &mut recorder.state
+    else {
+        return;
+    };
+
+    match recording.runs.get(*run) {
+        Some((mask, count)) => {
+            actions.set_incoming_mask(*mask);
+
+            *tick_in_run += 1;
+            if *tick_in_run >= *count {
+                *run += 1;
+                *tick_in_run = 0;
+            }
+        }
+        None => {
+            recorder.state = RecorderState::Idle;
+            events.send(ReplayFinished);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::actions::Action;
+
+    fn recording() -> InputRecording {
+        let mut recording = InputRecording {
+            seed: 0x0123_4567_89ab_cdef,
+            ..default()
+        };
+
+        for mask in [0, 0, 0, Action::Jump.bit(), Action::Jump.bit(), 0] {
+            recording.push(mask);
+        }
+
+        recording
+    }
+
+    fn bytes(recording: &InputRecording) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        recording.write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn pushes_runs() {
+        let recording = recording();
+
+        assert_eq!(
+            recording.runs,
+            vec![(0, 3), (Action::Jump.bit(), 2), (0, 1)]
+        );
+        assert_eq!(recording.ticks(), 6);
+    }
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let path =
+            std::env::temp_dir().join(format!("bgbr-roundtrip-{}.replay", std::process::id()));
+        let recording = recording();
+
+        recording.save(&path).unwrap();
+        let loaded = InputRecording::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap(), recording);
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut bytes = bytes(&recording());
+        bytes[0..4].copy_from_slice(b"NOPE");
+
+        let error = InputRecording::read_from(&mut bytes.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut bytes = bytes(&recording());
+        bytes[4] = VERSION + 1;
+
+        let error = InputRecording::read_from(&mut bytes.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let bytes = bytes(&recording());
+
+        // Within the header, the seed, the run count and the last run.
+        for len in [3, 10, 15, bytes.len() - 1] {
+            let error = InputRecording::read_from(&mut &bytes[..len]).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof, "{} bytes", len);
+        }
+    }
+
+    #[test]
+    fn rejects_empty_runs() {
+        let recording = InputRecording {
+            seed: 1,
+            runs: vec![(0, 2), (Action::Jump.bit(), 0)],
+        };
+
+        let error = InputRecording::read_from(&mut bytes(&recording).as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+use bevy::prelude::*;
+
+// A single seed for the whole game, split into named streams. Each stream only depends on the
+// seed and its name, so that drawing more loot does not change where obstacles spawn, and a run
+// can be replayed from its seed.
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRng>();
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct GameRng {
+    seed: u64,
+    streams: HashMap<String, RngStream>,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(GameRng::random_seed())
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    // Different on each launch, for runs that are not replayed.
+    pub fn random_seed() -> u64 {
+        RandomState::new().hash_one(0u64)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Restarts every stream from the new seed.
+    pub fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.streams.clear();
+    }
+
+    pub fn stream(&mut self, name: &str) -> &mut RngStream {
+        let seed = self.seed;
+
+        self.streams
+            .entry(name.to_string())
+            .or_insert_with(|| RngStream::new(seed ^ fnv1a(name)))
+    }
+}
+
+// FNV-1a, as the standard hasher is not guaranteed to be stable across releases.
+fn fnv1a(name: &str) -> u64 {
+    name.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+// SplitMix64, enough to spread spawns and loot around.
+#[derive(Debug, Clone)]
+pub struct RngStream {
+    state: u64,
+}
+
+impl RngStream {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // In [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(rng: &mut GameRng, name: &str, count: usize) -> Vec<u64> {
+        let stream = rng.stream(name);
+        (0..count).map(|_| stream.next_u64()).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_sequence() {
+        let mut first = GameRng::new(42);
+        let mut second = GameRng::new(42);
+
+        assert_eq!(draw(&mut first, "loot", 10), draw(&mut second, "loot", 10));
+    }
+
+    #[test]
+    fn reseeding_restarts_streams() {
+        let mut rng = GameRng::new(42);
+        let drawn = draw(&mut rng, "loot", 10);
+
+        rng.reseed(42);
+        assert_eq!(draw(&mut rng, "loot", 10), drawn);
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let mut rng = GameRng::new(42);
+        let loot = draw(&mut rng, "loot", 10);
+
+        let mut drawn_between = GameRng::new(42);
+        draw(&mut drawn_between, "jitter", 5);
+        let first = draw(&mut drawn_between, "loot", 5);
+        draw(&mut drawn_between, "jitter", 5);
+        let second = draw(&mut drawn_between, "loot", 5);
+
+        assert_eq!([first, second].concat(), loot);
+        assert_ne!(draw(&mut rng, "jitter", 10), loot);
+    }
+
+    #[test]
+    fn different_seeds_diverge() {
+        let mut first = GameRng::new(42);
+        let mut second = GameRng::new(43);
+
+        let first = draw(&mut first, "loot", 10);
+        let second = draw(&mut second, "loot", 10);
+        assert!(first.iter().zip(&second).all(|(a, b)| a != b));
+    }
+
+    #[test]
+    fn floats_stay_in_range() {
+        let mut stream = RngStream::new(42);
+
+        for _ in 0..1000 {
+            let value = stream.range(-2.0, 3.0);
+            assert!((-2.0..3.0).contains(&value));
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+
+// Loads any deserializable asset from RON files, such as `runner.tiers.ron` for
+// `RonAssetPlugin::<RunnerTiers>::new(&["tiers.ron"])`.
+//
+// Build with the `dev` feature to have the files reloaded as they are saved.
+pub struct RonAssetPlugin<A> {
+    extensions: &'static [&'static str],
+    asset: PhantomData<fn() -> A>,
+}
+
+impl<A> RonAssetPlugin<A> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            asset: PhantomData,
+        }
+    }
+}
+
+impl<A: Asset + DeserializeOwned> Plugin for RonAssetPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<A>()
+            .register_asset_loader(RonAssetLoader::<A> {
+                extensions: self.extensions,
+                asset: PhantomData,
+            });
+    }
+}
+
+pub struct RonAssetLoader<A> {
+    extensions: &'static [&'static str],
+    asset: PhantomData<fn() -> A>,
+}
+
+#[derive(Debug)]
+pub enum RonAssetError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for RonAssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RonAssetError::Io(error) => write!(f, "could not read RON asset: {}", error),
+            RonAssetError::Ron(error) => write!(f, "could not parse RON asset: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for RonAssetError {}
+
+impl<A: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = RonAssetError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<A, RonAssetError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(RonAssetError::Io)?;
+
+        ron::de::from_bytes(&bytes).map_err(RonAssetError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
@@ -0,0 +1,267 @@
+use bevy::prelude::*;
+
+// Points for the distance traveled, for clearing obstacles and for clearing them by a hair. Clears
+// in a row raise a multiplier on bonuses, until the player gets hit.
+//
+// Games tell `Scoring` what happened from their fixed systems, and listen to `ScoreChanged` for
+// popups and sounds.
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Scoring>()
+            .add_event::<ScoreChanged>()
+            .add_systems(FixedPostUpdate, send_score_events);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScoreSource {
+    Distance,
+    Clear,
+    NearMiss,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct ScoreChanged {
+    pub source: ScoreSource,
+    // Awarded, multiplier included.
+    pub points: u32,
+    pub multiplier: u32,
+    pub total: u32,
+}
+
+#[derive(Resource, Debug)]
+pub struct Scoring {
+    // Points per world unit traveled, usually below 1.0.
+    pub points_per_unit: f32,
+    pub clear_points: u32,
+    pub near_miss_points: u32,
+    // Clears in a row for the multiplier to go up by one.
+    pub clears_per_step: u32,
+    pub max_multiplier: u32,
+    total: u32,
+    // Fraction of a point left over from distance.
+    distance: f32,
+    streak: u32,
+    pending: Vec<ScoreChanged>,
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Self {
+            points_per_unit: 0.01,
+            clear_points: 100,
+            near_miss_points: 50,
+            clears_per_step: 5,
+            max_multiplier: 4,
+            total: 0,
+            distance: 0.0,
+            streak: 0,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl Scoring {
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    // Clears since the last hit.
+    pub fn streak(&self) -> u32 {
+        self.streak
+    }
+
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.streak / self.clears_per_step.max(1)).min(self.max_multiplier.max(1))
+    }
+
+    pub fn travel(&mut self, distance: f32) {
+        self.distance += distance * self.points_per_unit;
+
+        let points = self.distance.floor();
+        if points >= 1.0 {
+            self.distance -= points;
+            self.award(ScoreSource::Distance, points as u32, 1);
+        }
+    }
+
+    // The multiplier goes up after this clear is awarded.
+    pub fn clear(&mut self) {
+        let multiplier = self.multiplier();
+        self.award(
+            ScoreSource::Clear,
+            self.clear_points * multiplier,
+            multiplier,
+        );
+        self.streak += 1;
+    }
+
+    pub fn near_miss(&mut self) {
+        let multiplier = self.multiplier();
+        self.award(
+            ScoreSource::NearMiss,
+            self.near_miss_points * multiplier,
+            multiplier,
+        );
+    }
+
+    // Drops the multiplier back to 1.
+    pub fn hit(&mut self) {
+        self.streak = 0;
+    }
+
+    // For a new run, keeping the settings.
+    pub fn reset(&mut self) {
+        self.total = 0;
+        self.distance = 0.0;
+        self.streak = 0;
+        self.pending.clear();
+    }
+
+    fn award(&mut self, source: ScoreSource, points: u32, multiplier: u32) {
+        self.total = self.total.saturating_add(points);
+        self.pending.push(ScoreChanged {
+            source,
+            points,
+            multiplier,
+            total: self.total,
+        });
+    }
+}
+
+pub fn send_score_events(mut scoring: ResMut<Scoring>, mut events: EventWriter<ScoreChanged>) {
+    if scoring.pending.is_empty() {
+        return;
+    }
+
+    events.send_batch(std::mem::take(&mut scoring.pending));
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn clears(scoring: &mut Scoring, count: u32) {
+        for _ in 0..count {
+            scoring.clear();
+        }
+    }
+
+    #[test]
+    fn multiplier_grows_with_the_streak_up_to_the_max() {
+        let mut scoring = Scoring::default();
+        assert_eq!(scoring.multiplier(), 1);
+
+        clears(&mut scoring, 4);
+        assert_eq!(scoring.multiplier(), 1);
+        clears(&mut scoring, 1);
+        assert_eq!(scoring.multiplier(), 2);
+        clears(&mut scoring, 5);
+        assert_eq!(scoring.multiplier(), 3);
+        clears(&mut scoring, 20);
+        assert_eq!(scoring.streak(), 30);
+        assert_eq!(scoring.multiplier(), 4);
+    }
+
+    #[test]
+    fn clears_are_awarded_before_the_multiplier_goes_up() {
+        let mut scoring = Scoring::default();
+
+        clears(&mut scoring, 5);
+        assert_eq!(scoring.total(), 500);
+
+        scoring.clear();
+        assert_eq!(scoring.total(), 700);
+    }
+
+    #[test]
+    fn hit_drops_the_multiplier_and_keeps_the_score() {
+        let mut scoring = Scoring::default();
+        clears(&mut scoring, 7);
+        assert_eq!(scoring.multiplier(), 2);
+
+        scoring.hit();
+        assert_eq!(scoring.streak(), 0);
+        assert_eq!(scoring.multiplier(), 1);
+        assert_eq!(scoring.total(), 900);
+
+        scoring.clear();
+        assert_eq!(scoring.total(), 1_000);
+    }
+
+    #[test]
+    fn near_misses_use_the_multiplier_without_raising_it() {
+        let mut scoring = Scoring::default();
+
+        scoring.near_miss();
+        assert_eq!(scoring.total(), 50);
+
+        clears(&mut scoring, 5);
+        let total = scoring.total();
+        scoring.near_miss();
+        assert_eq!(scoring.total() - total, 100);
+        assert_eq!(scoring.streak(), 5);
+    }
+
+    #[test]
+    fn distance_adds_up_whole_points() {
+        let mut scoring = Scoring::default();
+
+        scoring.travel(60.0);
+        assert_eq!(scoring.total(), 0);
+        scoring.travel(60.0);
+        assert_eq!(scoring.total(), 1);
+        scoring.travel(280.0);
+        assert_eq!(scoring.total(), 4);
+    }
+
+    #[test]
+    fn reset_keeps_the_settings() {
+        let mut scoring = Scoring {
+            clear_points: 10,
+            ..default()
+        };
+        clears(&mut scoring, 6);
+        scoring.travel(150.0);
+
+        scoring.reset();
+        assert_eq!(scoring.total(), 0);
+        assert_eq!(scoring.multiplier(), 1);
+
+        scoring.travel(50.0);
+        scoring.clear();
+        assert_eq!(scoring.total(), 10);
+    }
+
+    #[test]
+    fn sends_events_in_order() {
+        let mut world = World::new();
+        world.init_resource::<Events<ScoreChanged>>();
+        let mut scoring = Scoring::default();
+        clears(&mut scoring, 5);
+        scoring.near_miss();
+        scoring.clear();
+        world.insert_resource(scoring);
+
+        world.run_system_once(send_score_events).unwrap();
+
+        let events = world.resource::<Events<ScoreChanged>>();
+        let sent: Vec<(ScoreSource, u32, u32, u32)> = events
+            .iter_current_update_events()
+            .map(|event| (event.source, event.points, event.multiplier, event.total))
+            .collect();
+        assert_eq!(sent.len(), 7);
+        assert_eq!(
+            sent[4..],
+            [
+                (ScoreSource::Clear, 100, 1, 500),
+                (ScoreSource::NearMiss, 100, 2, 600),
+                (ScoreSource::Clear, 200, 2, 800),
+            ]
+        );
+    }
+}
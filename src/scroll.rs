@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+
+// Moves every `Scrolling` entity towards -X at the world speed, so that speeding the world up,
+// slowing it down or pausing it is a single resource change.
+pub struct ScrollPlugin;
+
+impl Plugin for ScrollPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldScroll>()
+            .add_systems(FixedUpdate, scroll_entities.in_set(ScrollSet));
+    }
+}
+
+// Games can order scrolling relative to their own fixed systems, or add run conditions.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScrollSet;
+
+#[derive(Resource, Debug)]
+pub struct WorldScroll {
+    // Units per second.
+    pub base_speed: f32,
+    // For speed-ups driven by the game, such as difficulty.
+    pub speed_multiplier: f32,
+    // 1.0 at normal pace, below for slow motion, 0.0 to pause.
+    pub time_scale: f32,
+}
+
+impl Default for WorldScroll {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl WorldScroll {
+    pub fn new(base_speed: f32) -> Self {
+        Self {
+            base_speed,
+            speed_multiplier: 1.0,
+            time_scale: 1.0,
+        }
+    }
+
+    // Units per second, all factors applied.
+    pub fn speed(&self) -> f32 {
+        self.base_speed * self.speed_multiplier * self.time_scale
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.time_scale == 0.0
+    }
+}
+
+// Fraction of the world speed: 1.0 for the ground the player runs on, less for far away
+// elements, more for elements moving towards the player.
+#[derive(Component, Debug)]
+pub struct Scrolling {
+    pub factor: f32,
+}
+
+impl Default for Scrolling {
+    fn default() -> Self {
+        Self { factor: 1.0 }
+    }
+}
+
+pub fn scroll_entities(
+    mut query: Query<(&Scrolling, &mut Transform)>,
+    world_scroll: Res<WorldScroll>,
+    time: Res<Time>,
+) {
+    let distance = world_scroll.speed() * time.delta_secs();
+
+    for (scrolling, mut transform) in query.iter_mut() {
+        transform.translation.x -= distance * scrolling.factor;
+    }
+}
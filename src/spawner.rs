@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::pool::{EntityPool, PoolPlugin, Pooled};
+use crate::rng::{GameRng, RngPlugin, RngStream};
+
+pub struct SpawnerPlugin;
+
+impl Plugin for SpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RngPlugin>() {
+            app.add_plugins(RngPlugin);
+        }
+
+        if !app.is_plugin_added::<PoolPlugin>() {
+            app.add_plugins(PoolPlugin);
+        }
+
+        app.add_systems(FixedUpdate, tick_spawners.in_set(SpawnerSet));
+    }
+}
+
+// Games can order spawns relative to their own fixed systems, or add run conditions.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpawnerSet;
+
+pub struct SpawnContext<'a> {
+    pub spawner: Entity,
+    pub tier: &'a str,
+    pub position: Vec3,
+    // How many entities this tier spawned before this one.
+    pub count: u32,
+}
+
+// Receives the freshly spawned entity, already holding a `Transform` at the spawn position. Pooled
+// entities come back with nothing else, so the factory must insert everything again.
+pub type SpawnFactory = Arc<dyn Fn(&mut EntityCommands, &SpawnContext) + Send + Sync>;
+
+#[derive(Clone, Debug)]
+pub enum SpawnPosition {
+    // World position.
+    Fixed(Vec3),
+    // Relative to the spawner entity.
+    Offset(Vec3),
+    // Anywhere on the segment, in world coordinates.
+    Line { start: Vec3, end: Vec3 },
+    // Anywhere in the box, in world coordinates.
+    Area { min: Vec3, max: Vec3 },
+    // Between both radii around the spawner entity, on the XY plane.
+    Ring { min_radius: f32, max_radius: f32 },
+}
+
+#[derive(Clone)]
+pub struct SpawnTier {
+    pub name: String,
+    // Seconds between two spawns, set to `f32::INFINITY` for tiers only spawned on demand.
+    pub cooldown: f32,
+    // Up to that many seconds are randomly added to or removed from each cooldown.
+    pub jitter: f32,
+    // Applied to the cooldown after each spawn, below 1.0 to speed spawns up.
+    pub cooldown_multiplier: f32,
+    pub position: SpawnPosition,
+    pub factory: SpawnFactory,
+    // Locked tiers keep their cooldown where it is, but can still be spawned on demand.
+    pub active: bool,
+    // Reuses entities released to this `EntityPool` pool before spawning new ones.
+    pub pool: Option<String>,
+    remaining: f32,
+    count: u32,
+}
+
+impl SpawnTier {
+    pub fn new(
+        name: impl Into<String>,
+        cooldown: f32,
+        factory: impl Fn(&mut EntityCommands, &SpawnContext) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            cooldown,
+            jitter: 0.0,
+            cooldown_multiplier: 1.0,
+            position: SpawnPosition::Offset(Vec3::ZERO),
+            factory: Arc::new(factory),
+            active: true,
+            pool: None,
+            remaining: cooldown,
+            count: 0,
+        }
+    }
+
+    pub fn with_initial_delay(mut self, delay: f32) -> Self {
+        self.remaining = delay;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f32) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_cooldown_multiplier(mut self, multiplier: f32) -> Self {
+        self.cooldown_multiplier = multiplier;
+        self
+    }
+
+    pub fn with_pool(mut self, pool: impl Into<String>) -> Self {
+        self.pool = Some(pool.into());
+        self
+    }
+
+    pub fn at(mut self, position: SpawnPosition) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+#[derive(Component)]
+pub struct Spawner {
+    pub tiers: Vec<SpawnTier>,
+    pub active: bool,
+    // Cooldowns run down that many times faster, above 1.0 to speed spawns up.
+    pub rate: f32,
+    // Name of the `GameRng` stream drawn from for jitter and positions.
+    pub stream: String,
+    requested: Vec<String>,
+}
+
+impl Spawner {
+    pub fn new(tiers: Vec<SpawnTier>) -> Self {
+        Self {
+            tiers,
+            active: true,
+            rate: 1.0,
+            stream: "spawner".to_string(),
+            requested: Vec::new(),
+        }
+    }
+
+    pub fn with_stream(mut self, stream: impl Into<String>) -> Self {
+        self.stream = stream.into();
+        self
+    }
+
+    // Spawns from the named tier on the next tick, regardless of its cooldown.
+    pub fn spawn_now(&mut self, tier: &str) {
+        self.requested.push(tier.to_string());
+    }
+}
+
+fn spawn_position(position: &SpawnPosition, origin: Vec3, random: &mut RngStream) -> Vec3 {
+    match position {
+        SpawnPosition::Fixed(position) => *position,
+        SpawnPosition::Offset(offset) => origin + *offset,
+        SpawnPosition::Line { start, end } => start.lerp(*end, random.next_f32()),
+        SpawnPosition::Area { min, max } => Vec3::new(
+            random.range(min.x, max.x),
+            random.range(min.y, max.y),
+            random.range(min.z, max.z),
+        ),
+        SpawnPosition::Ring {
+            min_radius,
+            max_radius,
+        } => {
+            let angle = random.next_f32() * std::f32::consts::TAU;
+            let radius = random.range(*min_radius, *max_radius);
+            origin + Vec2::from_angle(angle).extend(0.0) * radius
+        }
+    }
+}
+
+pub fn tick_spawners(
+    mut commands: Commands,
+    mut spawners: Query<(Entity, &mut Spawner, Option<&GlobalTransform>)>,
+    mut rng: ResMut<GameRng>,
+    mut pool: ResMut<EntityPool>,
+    pooled: Query<(), With<Pooled>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+
+    for (entity, mut spawner, transform) in spawners.iter_mut() {
+        let origin = transform.map_or(Vec3::ZERO, GlobalTransform::translation);
+        let requested = std::mem::take(&mut spawner.requested);
+        let (active, rate) = (spawner.active, spawner.rate);
+        let random = rng.stream(&spawner.stream);
+
+        for tier in spawner.tiers.iter_mut() {
+            let mut spawns = requested.iter().filter(|name| **name == tier.name).count();
+
+            if active && tier.active {
+                tier.remaining -= dt * rate;
+
+                if tier.remaining <= 0.0 {
+                    spawns += 1;
+                    tier.cooldown *= tier.cooldown_multiplier;
+                    tier.remaining = tier.cooldown + random.range(-tier.jitter, tier.jitter);
+                }
+            }
+
+            for _ in 0..spawns {
+                let position = spawn_position(&tier.position, origin, random);
+                let context = SpawnContext {
+                    spawner: entity,
+                    tier: &tier.name,
+                    position,
+                    count: tier.count,
+                };
+
+                let transform = Transform::from_translation(position);
+                let reused = tier
+                    .pool
+                    .as_deref()
+                    .and_then(|name| pool.take(name, |entity| pooled.contains(entity)));
+
+                let mut spawned = match reused {
+                    Some(entity) => {
+                        let mut spawned = commands.entity(entity);
+                        spawned.insert((transform, Visibility::Inherited));
+                        spawned
+                    }
+                    None => commands.spawn(transform),
+                };
+
+                if let Some(name) = &tier.pool {
+                    spawned.insert(Pooled(name.clone()));
+                }
+
+                (tier.factory)(&mut spawned, &context);
+                tier.count += 1;
+            }
+        }
+    }
+}
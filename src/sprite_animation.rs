@@ -0,0 +1,482 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::aseprite::{Aseprite, AsepriteDirection, AsepriteTag};
+use crate::jump::{JumpPhase, JumpingState};
+
+// Flipbook animations on texture atlas sprites: named clips of atlas indices, each frame with its
+// own duration, switched by game code or by the jump state.
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpriteFrameEvent>()
+            .add_event::<SpriteAnimationFinished>()
+            .add_systems(Update, (switch_jump_animations, animate_sprites).chain());
+    }
+}
+
+// Sent when a frame marked with `SpriteClip::with_event` is shown, for footsteps and the like.
+#[derive(Event, Debug)]
+pub struct SpriteFrameEvent {
+    pub entity: Entity,
+    pub clip: String,
+    pub name: String,
+}
+
+// Sent when a `PlaybackMode::Once` clip shows its last frame for its whole duration.
+#[derive(Event, Debug)]
+pub struct SpriteAnimationFinished {
+    pub entity: Entity,
+    pub clip: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackMode {
+    Loop,
+    // Stays on the last frame.
+    Once,
+    // Back and forth, without showing the ends twice.
+    PingPong,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpriteClip {
+    // Atlas indices.
+    frames: Vec<usize>,
+    durations: Vec<Duration>,
+    mode: PlaybackMode,
+    // By position in the clip.
+    events: Vec<(usize, String)>,
+}
+
+impl SpriteClip {
+    pub fn new(frames: impl IntoIterator<Item = usize>, frame_duration: Duration) -> Self {
+        let frames: Vec<usize> = frames.into_iter().collect();
+
+        Self {
+            durations: vec![frame_duration; frames.len()],
+            frames,
+            mode: PlaybackMode::Loop,
+            events: Vec::new(),
+        }
+    }
+
+    // From a tag, with the durations set in Aseprite. Tags played a set number of times play
+    // once here. Frames past the end of the file are left out.
+    pub fn from_aseprite_tag(aseprite: &Aseprite, tag: &AsepriteTag) -> Self {
+        let last = aseprite.frame_durations.len().checked_sub(1);
+        let mut frames: Vec<usize> = match last {
+            Some(last) => (tag.from..=tag.to.min(last)).collect(),
+            None => Vec::new(),
+        };
+        if matches!(
+            tag.direction,
+            AsepriteDirection::Reverse | AsepriteDirection::PingPongReverse
+        ) {
+            frames.reverse();
+        }
+
+        let mode = match tag.direction {
+            AsepriteDirection::PingPong | AsepriteDirection::PingPongReverse => {
+                PlaybackMode::PingPong
+            }
+            _ if tag.repeat == 0 => PlaybackMode::Loop,
+            _ => PlaybackMode::Once,
+        };
+
+        Self {
+            durations: frames
+                .iter()
+                .map(|frame| aseprite.frame_durations[*frame])
+                .collect(),
+            frames,
+            mode,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn with_mode(mut self, mode: PlaybackMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    // One per frame, replacing the uniform duration.
+    pub fn with_durations(mut self, durations: impl IntoIterator<Item = Duration>) -> Self {
+        self.durations = durations.into_iter().collect();
+        self.durations.resize(
+            self.frames.len(),
+            self.durations.last().copied().unwrap_or_default(),
+        );
+        self
+    }
+
+    // `position` counts from the first frame of the clip, not in the atlas.
+    pub fn with_event(mut self, position: usize, name: &str) -> Self {
+        self.events.push((position, name.to_string()));
+        self
+    }
+
+    fn next_position(&self, position: usize, backwards: bool) -> Option<(usize, bool)> {
+        let last = self.frames.len().saturating_sub(1);
+
+        match self.mode {
+            PlaybackMode::Loop => Some(((position + 1) % self.frames.len(), false)),
+            PlaybackMode::Once => (position < last).then_some((position + 1, false)),
+            PlaybackMode::PingPong if last == 0 => Some((0, false)),
+            PlaybackMode::PingPong if backwards && position == 0 => Some((1, false)),
+            PlaybackMode::PingPong if backwards => Some((position - 1, true)),
+            PlaybackMode::PingPong if position == last => Some((last - 1, true)),
+            PlaybackMode::PingPong => Some((position + 1, false)),
+        }
+    }
+}
+
+// Needs a `Sprite` with a texture atlas, whose index is taken over.
+#[derive(Component, Debug)]
+#[require(Sprite)]
+pub struct SpriteAnimation {
+    clips: HashMap<String, SpriteClip>,
+    current: Option<String>,
+    position: usize,
+    elapsed: Duration,
+    backwards: bool,
+    // Whether events of the current frame were sent.
+    entered: bool,
+    finished: bool,
+    speed: f32,
+}
+
+impl Default for SpriteAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpriteAnimation {
+    pub fn new() -> Self {
+        Self {
+            clips: HashMap::new(),
+            current: None,
+            position: 0,
+            elapsed: Duration::ZERO,
+            backwards: false,
+            entered: false,
+            finished: false,
+            speed: 1.0,
+        }
+    }
+
+    // One clip per tag, named after it.
+    pub fn from_aseprite(aseprite: &Aseprite) -> Self {
+        aseprite
+            .tags
+            .iter()
+            .fold(Self::new(), |animation, (name, tag)| {
+                animation.with_clip(name, SpriteClip::from_aseprite_tag(aseprite, tag))
+            })
+    }
+
+    pub fn with_clip(mut self, name: &str, clip: SpriteClip) -> Self {
+        if clip.frames.is_empty() {
+            warn!("Sprite clip {} has no frames", name);
+            return self;
+        }
+
+        self.clips.insert(name.to_string(), clip);
+        self
+    }
+
+    // Adds an event to a clip already there, see `SpriteClip::with_event`.
+    pub fn add_event(&mut self, clip: &str, position: usize, name: &str) {
+        match self.clips.get_mut(clip) {
+            Some(clip) => clip.events.push((position, name.to_string())),
+            None => warn!("No sprite clip named {} for event {}", clip, name),
+        }
+    }
+
+    // Takes the clips of `other`, for instance once their file is loaded again, and restarts the
+    // current clip.
+    pub fn replace_clips(&mut self, other: SpriteAnimation) {
+        self.clips = other.clips;
+
+        if let Some(clip) = self.current.take() {
+            self.restart(&clip);
+        }
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // Keeps playing when `clip` is already playing, restarts it when it finished.
+    pub fn play(&mut self, clip: &str) {
+        if self.current() == Some(clip) && !self.finished {
+            return;
+        }
+
+        self.restart(clip);
+    }
+
+    pub fn restart(&mut self, clip: &str) {
+        if !self.clips.contains_key(clip) {
+            // Otherwise the clips may still be loading, see `AsepriteAnimation`.
+            if !self.clips.is_empty() {
+                warn_once!("No sprite clip named {}", clip);
+            }
+            return;
+        }
+
+        self.current = Some(clip.to_string());
+        self.position = 0;
+        self.elapsed = Duration::ZERO;
+        self.backwards = false;
+        self.entered = false;
+        self.finished = false;
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+}
+
+pub fn animate_sprites(
+    mut query: Query<(Entity, &mut SpriteAnimation, &mut Sprite)>,
+    mut frame_events: EventWriter<SpriteFrameEvent>,
+    mut finished_events: EventWriter<SpriteAnimationFinished>,
+    time: Res<Time>,
+) {
+    for (entity, mut animation, mut sprite) in query.iter_mut() {
+        let animation = &mut *animation;
+
+        let Some((name, clip)) = animation
+            .current
+            .as_ref()
+            .and_then(|name| Some((name, animation.clips.get(name)?)))
+        else {
+            continue;
+        };
+
+        if !animation.finished {
+            animation.elapsed += time.delta().mul_f32(animation.speed);
+        }
+
+        // Several frames can go by in a single update, each sends its events.
+        loop {
+            if !animation.entered {
+                animation.entered = true;
+                frame_events.send_batch(
+                    clip.events
+                        .iter()
+                        .filter(|(position, _)| *position == animation.position)
+                        .map(|(_, event)| SpriteFrameEvent {
+                            entity,
+                            clip: name.clone(),
+                            name: event.clone(),
+                        }),
+                );
+            }
+
+            let duration = clip.durations[animation.position];
+            if animation.finished || duration.is_zero() || animation.elapsed < duration {
+                break;
+            }
+
+            animation.elapsed -= duration;
+
+            match clip.next_position(animation.position, animation.backwards) {
+                Some((position, backwards)) => {
+                    animation.position = position;
+                    animation.backwards = backwards;
+                    animation.entered = false;
+                }
+                None => {
+                    animation.finished = true;
+                    finished_events.send(SpriteAnimationFinished {
+                        entity,
+                        clip: name.clone(),
+                    });
+                }
+            }
+        }
+
+        let index = clip.frames[animation.position];
+        if let Some(atlas) = sprite.texture_atlas.as_mut() {
+            if atlas.index != index {
+                atlas.index = index;
+            }
+        }
+    }
+}
+
+// Plays clips following the `JumpingState` on the same entity. The landing clip should play
+// once, running resumes when it is over.
+#[derive(Component, Debug)]
+#[require(SpriteAnimation)]
+pub struct JumpAnimations {
+    pub run: String,
+    pub jump: String,
+    pub fall: String,
+    pub land: String,
+    last_phase: JumpPhase,
+}
+
+impl JumpAnimations {
+    pub fn new(run: &str, jump: &str, fall: &str, land: &str) -> Self {
+        Self {
+            run: run.to_string(),
+            jump: jump.to_string(),
+            fall: fall.to_string(),
+            land: land.to_string(),
+            last_phase: JumpPhase::Grounded,
+        }
+    }
+}
+
+pub fn switch_jump_animations(
+    mut query: Query<(&mut JumpAnimations, &JumpingState, &mut SpriteAnimation)>,
+) {
+    for (mut clips, jumping_state, mut animation) in query.iter_mut() {
+        let phase = jumping_state.phase();
+        let landed = phase == JumpPhase::Grounded && clips.last_phase != JumpPhase::Grounded;
+        let landing = animation.current() == Some(clips.land.as_str()) && !animation.is_finished();
+
+        let clip = match phase {
+            JumpPhase::Rising => &clips.jump,
+            JumpPhase::Falling => &clips.fall,
+            JumpPhase::Grounded if landed => &clips.land,
+            JumpPhase::Grounded if landing => &clips.land,
+            JumpPhase::Grounded => &clips.run,
+        };
+
+        if landed {
+            animation.restart(clip);
+        } else if animation.current() != Some(clip.as_str()) {
+            animation.play(clip);
+        }
+
+        clips.last_phase = phase;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aseprite(frame_count: usize) -> Aseprite {
+        Aseprite {
+            image: Handle::default(),
+            layout: Handle::default(),
+            frame_size: UVec2::splat(24),
+            frame_durations: (1..=frame_count as u64)
+                .map(|index| Duration::from_millis(index * 10))
+                .collect(),
+            tags: HashMap::new(),
+        }
+    }
+
+    fn tag(from: usize, to: usize, direction: AsepriteDirection) -> AsepriteTag {
+        AsepriteTag {
+            from,
+            to,
+            direction,
+            repeat: 0,
+        }
+    }
+
+    // Positions and directions visited from the first frame.
+    fn steps(clip: &SpriteClip, count: usize) -> Vec<Option<(usize, bool)>> {
+        let mut step = Some((0, false));
+        (0..count)
+            .map(|_| {
+                step =
+                    step.and_then(|(position, backwards)| clip.next_position(position, backwards));
+                step
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loop_wraps_around() {
+        let clip = SpriteClip::new(0..3, Duration::ZERO);
+
+        assert_eq!(
+            steps(&clip, 4),
+            vec![
+                Some((1, false)),
+                Some((2, false)),
+                Some((0, false)),
+                Some((1, false))
+            ]
+        );
+    }
+
+    #[test]
+    fn once_stops_on_the_last_frame() {
+        let clip = SpriteClip::new(0..3, Duration::ZERO).with_mode(PlaybackMode::Once);
+
+        assert_eq!(
+            steps(&clip, 3),
+            vec![Some((1, false)), Some((2, false)), None]
+        );
+        assert_eq!(clip.next_position(2, false), None);
+    }
+
+    #[test]
+    fn ping_pong_does_not_repeat_the_ends() {
+        let clip = SpriteClip::new(0..3, Duration::ZERO).with_mode(PlaybackMode::PingPong);
+
+        assert_eq!(
+            steps(&clip, 6),
+            vec![
+                Some((1, false)),
+                Some((2, false)),
+                Some((1, true)),
+                Some((0, true)),
+                Some((1, false)),
+                Some((2, false))
+            ]
+        );
+    }
+
+    #[test]
+    fn single_frame_ping_pong_stays_put() {
+        let clip = SpriteClip::new([5], Duration::ZERO).with_mode(PlaybackMode::PingPong);
+
+        assert_eq!(steps(&clip, 3), vec![Some((0, false)); 3]);
+    }
+
+    #[test]
+    fn clips_from_tags_use_their_durations() {
+        let clip =
+            SpriteClip::from_aseprite_tag(&aseprite(4), &tag(1, 3, AsepriteDirection::Reverse));
+
+        assert_eq!(clip.frames, vec![3, 2, 1]);
+        assert_eq!(
+            clip.durations,
+            [40, 30, 20].map(Duration::from_millis).to_vec()
+        );
+        assert_eq!(clip.mode, PlaybackMode::Loop);
+    }
+
+    #[test]
+    fn clips_from_tags_stop_at_the_last_frame() {
+        let aseprite = aseprite(4);
+
+        let clip = SpriteClip::from_aseprite_tag(&aseprite, &tag(2, 9, AsepriteDirection::Forward));
+        assert_eq!(clip.frames, vec![2, 3]);
+        assert_eq!(clip.durations.len(), 2);
+
+        let clip = SpriteClip::from_aseprite_tag(&aseprite, &tag(5, 9, AsepriteDirection::Forward));
+        assert!(clip.frames.is_empty());
+
+        let animation = SpriteAnimation::new().with_clip("past", clip);
+        assert!(animation.clips.is_empty());
+    }
+}
@@ -0,0 +1,404 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+use crate::actions::{Action, Actions, ActionsPlugin, ActionsSet};
+
+const IDLE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.2);
+const PRESSED_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.5);
+const KNOB_SIZE: f32 = 0.4;
+
+pub struct VirtualControlsPlugin;
+
+impl Plugin for VirtualControlsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<ActionsPlugin>() {
+            app.add_plugins(ActionsPlugin);
+        }
+
+        // Every frame, as frames may run no fixed tick at all: a tap shorter than a tick is still
+        // seen as a press.
+        app.add_systems(
+            PreUpdate,
+            (
+                update_virtual_buttons,
+                update_virtual_joysticks,
+                latch_virtual_actions,
+            )
+                .chain()
+                .after(InputSystem),
+        )
+        .add_systems(Update, update_virtual_controls_visuals)
+        .add_systems(
+            FixedPreUpdate,
+            press_virtual_actions.in_set(ActionsSet::Collect),
+        );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VirtualPointer {
+    Mouse,
+    Touch(u64),
+}
+
+#[derive(Component)]
+pub struct VirtualButton {
+    pub action: Action,
+    pressed: bool,
+    just_pressed: bool,
+}
+
+impl VirtualButton {
+    pub fn new(action: Action) -> Self {
+        Self {
+            action,
+            pressed: false,
+            just_pressed: false,
+        }
+    }
+
+    pub fn pressed(&self) -> bool {
+        self.pressed
+    }
+
+    // Pressed during the latest frame.
+    pub fn just_pressed(&self) -> bool {
+        self.just_pressed
+    }
+}
+
+// A stick following the pointer that grabbed it, even once it leaves the joystick area.
+// Its value is in [-1, 1] on both axes, Y pointing up.
+#[derive(Component)]
+pub struct VirtualJoystick {
+    pub deadzone: f32,
+    pointer: Option<VirtualPointer>,
+    value: Vec2,
+}
+
+impl VirtualJoystick {
+    pub fn new(deadzone: f32) -> Self {
+        Self {
+            deadzone,
+            pointer: None,
+            value: Vec2::ZERO,
+        }
+    }
+
+    pub fn value(&self) -> Vec2 {
+        self.value
+    }
+}
+
+#[derive(Component)]
+pub struct VirtualJoystickKnob;
+
+pub fn spawn_virtual_button(
+    commands: &mut Commands,
+    action: Action,
+    label: &str,
+    node: Node,
+) -> Entity {
+    commands
+        .spawn((
+            VirtualButton::new(action),
+            Node {
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..node
+            },
+            BackgroundColor(IDLE_COLOR),
+            BorderRadius::MAX,
+        ))
+        .with_children(|parent| {
+            parent.spawn((Text::new(label), TextColor(Color::WHITE)));
+        })
+        .id()
+}
+
+pub fn spawn_virtual_joystick(commands: &mut Commands, deadzone: f32, node: Node) -> Entity {
+    commands
+        .spawn((
+            VirtualJoystick::new(deadzone),
+            node,
+            BackgroundColor(IDLE_COLOR),
+            BorderRadius::MAX,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                VirtualJoystickKnob,
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(KNOB_SIZE * 100.0),
+                    height: Val::Percent(KNOB_SIZE * 100.0),
+                    left: Val::Percent((1.0 - KNOB_SIZE) * 50.0),
+                    top: Val::Percent((1.0 - KNOB_SIZE) * 50.0),
+                    ..default()
+                },
+                BackgroundColor(PRESSED_COLOR),
+                BorderRadius::MAX,
+            ));
+        })
+        .id()
+}
+
+// Held pointers in logical window coordinates, along with whether they were just pressed.
+fn held_pointers(
+    window: &Window,
+    mouse: &ButtonInput<MouseButton>,
+    touches: &Touches,
+) -> Vec<(VirtualPointer, Vec2, bool)> {
+    let mut pointers: Vec<(VirtualPointer, Vec2, bool)> = touches
+        .iter()
+        .map(|touch| {
+            (
+                VirtualPointer::Touch(touch.id()),
+                touch.position(),
+                touches.just_pressed(touch.id()),
+            )
+        })
+        .collect();
+
+    if mouse.pressed(MouseButton::Left) {
+        if let Some(position) = window.cursor_position() {
+            pointers.push((
+                VirtualPointer::Mouse,
+                position,
+                mouse.just_pressed(MouseButton::Left),
+            ));
+        }
+    }
+
+    pointers
+}
+
+fn node_rect(node: &ComputedNode, transform: &GlobalTransform) -> Rect {
+    let scale = node.inverse_scale_factor();
+    Rect::from_center_size(
+        transform.translation().truncate() * scale,
+        node.size() * scale,
+    )
+}
+
+pub fn update_virtual_buttons(
+    window: Single<&Window>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    mut buttons: Query<(&mut VirtualButton, &ComputedNode, &GlobalTransform)>,
+) {
+    let pointers = held_pointers(&window, &mouse, &touches);
+
+    for (mut button, node, transform) in buttons.iter_mut() {
+        let rect = node_rect(node, transform);
+        let pressed = pointers
+            .iter()
+            .any(|(_, position, _)| rect.contains(*position));
+
+        let just_pressed = pressed && !button.pressed;
+
+        if button.pressed != pressed || button.just_pressed != just_pressed {
+            button.pressed = pressed;
+            button.just_pressed = just_pressed;
+        }
+    }
+}
+
+pub fn update_virtual_joysticks(
+    window: Single<&Window>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    mut joysticks: Query<(&mut VirtualJoystick, &ComputedNode, &GlobalTransform)>,
+) {
+    let pointers = held_pointers(&window, &mouse, &touches);
+
+    for (mut joystick, node, transform) in joysticks.iter_mut() {
+        let rect = node_rect(node, transform);
+
+        let grabbed = joystick
+            .pointer
+            .and_then(|pointer| pointers.iter().find(|(id, _, _)| *id == pointer))
+            .or_else(|| {
+                pointers
+                    .iter()
+                    .find(|(_, position, just_pressed)| *just_pressed && rect.contains(*position))
+            });
+
+        match grabbed {
+            Some((pointer, position, _)) => {
+                let offset = (*position - rect.center()) / rect.half_size();
+                joystick.pointer = Some(*pointer);
+                joystick.value = Vec2::new(offset.x, -offset.y).clamp_length_max(1.0);
+            }
+            None => {
+                joystick.pointer = None;
+                joystick.value = Vec2::ZERO;
+            }
+        }
+    }
+}
+
+pub fn update_virtual_controls_visuals(
+    mut buttons: Query<(&VirtualButton, &mut BackgroundColor), Changed<VirtualButton>>,
+    joysticks: Query<(&VirtualJoystick, &Children), Changed<VirtualJoystick>>,
+    mut knobs: Query<&mut Node, With<VirtualJoystickKnob>>,
+) {
+    for (button, mut color) in buttons.iter_mut() {
+        color.0 = if button.pressed {
+            PRESSED_COLOR
+        } else {
+            IDLE_COLOR
+        };
+    }
+
+    for (joystick, children) in joysticks.iter() {
+        for child in children.iter() {
+            let Ok(mut knob) = knobs.get_mut(*child) else {
+                continue;
+            };
+
+            let offset = (1.0 - KNOB_SIZE) * 50.0;
+            knob.left = Val::Percent(offset + joystick.value.x * offset);
+            knob.top = Val::Percent(offset - joystick.value.y * offset);
+        }
+    }
+}
+
+pub fn latch_virtual_actions(mut actions: ResMut<Actions>, buttons: Query<&VirtualButton>) {
+    for button in buttons.iter() {
+        if button.just_pressed {
+            actions.latch(button.action);
+        }
+    }
+}
+
+pub fn press_virtual_actions(
+    mut actions: ResMut<Actions>,
+    buttons: Query<&VirtualButton>,
+    joysticks: Query<&VirtualJoystick>,
+) {
+    for button in buttons.iter() {
+        if button.pressed {
+            actions.press(button.action);
+        }
+    }
+
+    for joystick in joysticks.iter() {
+        let value = joystick.value;
+
+        if value.length() < joystick.deadzone {
+            continue;
+        }
+
+        for (pressed, action) in [
+            (value.x < -joystick.deadzone, Action::Left),
+            (value.x > joystick.deadzone, Action::Right),
+            (value.y > joystick.deadzone, Action::Up),
+            (value.y < -joystick.deadzone, Action::Down),
+        ] {
+            if pressed {
+                actions.press(action);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::actions::{collect_keyboard_actions, commit_actions, ActionBindings};
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Actions>();
+        world.init_resource::<ActionBindings>();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world
+    }
+
+    fn tick(world: &mut World) {
+        world.run_system_once(collect_keyboard_actions).unwrap();
+        world.run_system_once(press_virtual_actions).unwrap();
+        world.run_system_once(commit_actions).unwrap();
+    }
+
+    fn set_button(world: &mut World, entity: Entity, pressed: bool) {
+        let mut button = world.get_mut::<VirtualButton>(entity).unwrap();
+        button.just_pressed = pressed && !button.pressed;
+        button.pressed = pressed;
+    }
+
+    fn pressed_by_joystick(value: Vec2) -> Vec<Action> {
+        let mut world = world();
+        let mut joystick = VirtualJoystick::new(0.2);
+        joystick.value = value;
+        world.spawn(joystick);
+
+        tick(&mut world);
+
+        let actions = world.resource::<Actions>();
+        Action::ALL
+            .into_iter()
+            .filter(|action| actions.pressed(*action))
+            .collect()
+    }
+
+    #[test]
+    fn joystick_ignores_the_deadzone() {
+        assert_eq!(pressed_by_joystick(Vec2::ZERO), []);
+        assert_eq!(pressed_by_joystick(Vec2::new(0.1, -0.1)), []);
+    }
+
+    #[test]
+    fn joystick_presses_directions_past_the_deadzone() {
+        assert_eq!(pressed_by_joystick(Vec2::new(-0.5, 0.0)), [Action::Left]);
+        assert_eq!(pressed_by_joystick(Vec2::new(0.0, -0.5)), [Action::Down]);
+        // Barely off the axis, within the deadzone on the other one.
+        assert_eq!(pressed_by_joystick(Vec2::new(0.6, 0.1)), [Action::Right]);
+        assert_eq!(
+            pressed_by_joystick(Vec2::new(0.5, 0.5)),
+            [Action::Up, Action::Right]
+        );
+    }
+
+    #[test]
+    fn held_button_is_pressed_once() {
+        let mut world = world();
+        let button = world.spawn(VirtualButton::new(Action::Jump)).id();
+
+        set_button(&mut world, button, true);
+        world.run_system_once(latch_virtual_actions).unwrap();
+        tick(&mut world);
+        assert!(world.resource::<Actions>().just_pressed(Action::Jump));
+
+        set_button(&mut world, button, true);
+        world.run_system_once(latch_virtual_actions).unwrap();
+        tick(&mut world);
+        let actions = world.resource::<Actions>();
+        assert!(actions.pressed(Action::Jump));
+        assert!(!actions.just_pressed(Action::Jump));
+
+        set_button(&mut world, button, false);
+        tick(&mut world);
+        assert!(world.resource::<Actions>().just_released(Action::Jump));
+    }
+
+    #[test]
+    fn tap_between_ticks_is_latched() {
+        let mut world = world();
+        let button = world.spawn(VirtualButton::new(Action::Jump)).id();
+
+        // Pressed and released by frames that ran no fixed tick.
+        set_button(&mut world, button, true);
+        world.run_system_once(latch_virtual_actions).unwrap();
+        set_button(&mut world, button, false);
+        world.run_system_once(latch_virtual_actions).unwrap();
+
+        tick(&mut world);
+        assert!(world.resource::<Actions>().just_pressed(Action::Jump));
+
+        tick(&mut world);
+        assert!(!world.resource::<Actions>().pressed(Action::Jump));
+    }
+}
@@ -0,0 +1,127 @@
+// Spawns and releases pooled entities for a long simulated while, restarting every so often the
+// way a game restarts a run. Entity and asset counts must stay flat once the first entities come
+// back to the pool.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use bevy::asset::AssetPlugin;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+
+use bevy_game_bits::pool::{HandleCache, ReleaseExt};
+use bevy_game_bits::spawner::{SpawnTier, Spawner, SpawnerPlugin, SpawnerSet};
+
+// Ten minutes at 60 frames per second.
+const FRAMES: u32 = 36_000;
+const FRAME_TIME: Duration = Duration::from_nanos(16_666_667);
+const FRAMES_PER_RUN: u32 = 3_600;
+
+const SPAWN_COOLDOWN: f32 = 0.05;
+const LIFETIME: f32 = 2.0;
+
+#[derive(Component)]
+struct Lifetime(f32);
+
+// Every orb entity ever spawned, which only grows when the pool has none to give back.
+#[derive(Resource, Default)]
+struct SpawnedOrbs(HashSet<Entity>);
+
+#[derive(Debug, PartialEq)]
+struct Counts {
+    entities: u32,
+    spawned_orbs: usize,
+    meshes: usize,
+    materials: usize,
+}
+
+fn counts(world: &World) -> Counts {
+    Counts {
+        entities: world.entities().len(),
+        spawned_orbs: world.resource::<SpawnedOrbs>().0.len(),
+        meshes: world.resource::<Assets<Mesh>>().len(),
+        materials: world.resource::<Assets<ColorMaterial>>().len(),
+    }
+}
+
+#[test]
+fn pooled_spawns_stay_flat_across_runs() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), SpawnerPlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .init_resource::<HandleCache<Mesh>>()
+        .init_resource::<HandleCache<ColorMaterial>>()
+        .init_resource::<SpawnedOrbs>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME_TIME))
+        .add_systems(FixedUpdate, (track_orbs, expire).chain().after(SpawnerSet));
+    app.finish();
+    app.cleanup();
+
+    // At the end of each run.
+    let mut runs = Vec::new();
+
+    for frame in 0..FRAMES {
+        if frame % FRAMES_PER_RUN == 0 {
+            app.world_mut().run_system_cached(restart).unwrap();
+        }
+
+        app.update();
+
+        if (frame + 1) % FRAMES_PER_RUN == 0 {
+            runs.push(counts(app.world()));
+        }
+    }
+
+    assert!(runs[0].spawned_orbs > 1, "runs: {:?}", runs);
+    // The first run fills the pool, the others only reuse it.
+    for run in runs.iter().skip(1) {
+        assert_eq!(*run, runs[1], "runs: {:?}", runs);
+    }
+    assert_eq!((runs[0].meshes, runs[0].materials), (1, 1));
+}
+
+// Despawns what is left of the previous run, then spawns the spawner again with the same assets.
+fn restart(
+    mut commands: Commands,
+    spawners: Query<Entity, With<Spawner>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut mesh_cache: ResMut<HandleCache<Mesh>>,
+    mut material_cache: ResMut<HandleCache<ColorMaterial>>,
+) {
+    for entity in spawners.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let mesh = mesh_cache.get_or_add("orb", &mut meshes, || Rectangle::new(4.0, 4.0).into());
+    let material =
+        material_cache.get_or_add("orb", &mut materials, || Color::srgb(1.0, 0.84, 0.0).into());
+
+    commands.spawn(Spawner::new(vec![SpawnTier::new(
+        "orb",
+        SPAWN_COOLDOWN,
+        move |entity, _context| {
+            entity.insert((
+                Lifetime(LIFETIME),
+                Mesh2d(mesh.clone()),
+                MeshMaterial2d(material.clone()),
+            ));
+        },
+    )
+    .with_pool("orb")]));
+}
+
+fn track_orbs(mut spawned: ResMut<SpawnedOrbs>, orbs: Query<Entity, Added<Lifetime>>) {
+    spawned.0.extend(orbs.iter());
+}
+
+fn expire(mut commands: Commands, mut query: Query<(Entity, &mut Lifetime)>, time: Res<Time>) {
+    for (entity, mut lifetime) in query.iter_mut() {
+        lifetime.0 -= time.delta_secs();
+
+        if lifetime.0 <= 0.0 {
+            commands.entity(entity).release();
+        }
+    }
+}